@@ -0,0 +1,320 @@
+use anyhow::Result;
+use clap::{Subcommand, ValueEnum};
+use evefrontier_lib::centrality::rank_by;
+use evefrontier_lib::components::region_label;
+use evefrontier_lib::export::region_systems;
+use evefrontier_lib::{Centrality, CentralityConfig, CentralityMetric, Starmap, System};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::OutputFormat;
+
+#[derive(Subcommand)]
+pub enum AnalyzeCommand {
+    /// Systems and gates whose loss would split the network, ranked by how much they cut off
+    Chokepoints {
+        /// Only report chokepoints in this region (name or id); a gate counts when either end
+        /// is in it
+        #[arg(long)]
+        region: Option<String>,
+        /// Show at most N of each kind; 0 shows all
+        #[arg(long, default_value_t = 20, value_name = "N")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Systems ranked by centrality, as candidates for staging and trade hubs
+    Hubs {
+        /// The measure to rank by
+        #[arg(long, value_enum, default_value_t = Metric::Betweenness)]
+        by: Metric,
+        /// Show the N highest-ranked systems, or the N highest in each region with --per-region
+        #[arg(long, default_value_t = 10, value_name = "N")]
+        top: usize,
+        /// Rank the systems of each region separately
+        #[arg(long)]
+        per_region: bool,
+        /// Only rank the systems of this region (name or id); centrality is still measured
+        /// over the whole map
+        #[arg(long)]
+        region: Option<String>,
+        /// Estimate betweenness and closeness from searches out of K random systems instead of
+        /// all of them; much faster on the full map
+        #[arg(long, value_name = "K", value_parser = clap::value_parser!(u64).range(1..))]
+        samples: Option<u64>,
+        /// Seed for choosing the --samples systems
+        #[arg(long, default_value_t = 0, requires = "samples")]
+        seed: u64,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Metric {
+    Degree,
+    Betweenness,
+    Closeness,
+}
+
+impl From<Metric> for CentralityMetric {
+    fn from(metric: Metric) -> Self {
+        match metric {
+            Metric::Degree => CentralityMetric::Degree,
+            Metric::Betweenness => CentralityMetric::Betweenness,
+            Metric::Closeness => CentralityMetric::Closeness,
+        }
+    }
+}
+
+impl Metric {
+    fn name(self) -> &'static str {
+        match self {
+            Metric::Degree => "degree",
+            Metric::Betweenness => "betweenness",
+            Metric::Closeness => "closeness",
+        }
+    }
+}
+
+pub fn run(command: AnalyzeCommand, starmap: &Starmap) -> Result<()> {
+    match command {
+        AnalyzeCommand::Chokepoints {
+            region,
+            limit,
+            format,
+        } => chokepoints(starmap, region.as_deref(), limit, format),
+        AnalyzeCommand::Hubs {
+            by,
+            top,
+            per_region,
+            region,
+            samples,
+            seed,
+            format,
+        } => hubs(
+            starmap,
+            HubOptions {
+                by,
+                top,
+                per_region,
+                region,
+                config: CentralityConfig {
+                    samples: samples.map(|k| k as usize),
+                    seed,
+                },
+                format,
+            },
+        ),
+    }
+}
+
+fn system_json(system: &System) -> Value {
+    json!({ "id": system.id, "name": system.name, "region": system.region })
+}
+
+fn system_text(system: &System) -> String {
+    format!("{} (id={})", system.name, system.id)
+}
+
+/// The first `limit` items, or all of them when `limit` is 0.
+fn take<T>(items: Vec<T>, limit: usize) -> Vec<T> {
+    if limit == 0 {
+        items
+    } else {
+        items.into_iter().take(limit).collect()
+    }
+}
+
+fn chokepoints(
+    starmap: &Starmap,
+    region: Option<&str>,
+    limit: usize,
+    format: OutputFormat,
+) -> Result<()> {
+    let selected: Option<HashSet<usize>> = match region {
+        Some(region) => Some(
+            region_systems(&starmap.systems, region)?
+                .into_iter()
+                .collect(),
+        ),
+        None => None,
+    };
+    let included = |idx: usize| selected.as_ref().is_none_or(|s| s.contains(&idx));
+
+    let found = evefrontier_lib::find_chokepoints(&starmap.graph);
+    let points: Vec<_> = found
+        .articulation_points
+        .into_iter()
+        .filter(|p| included(p.system))
+        .collect();
+    let bridges: Vec<_> = found
+        .bridges
+        .into_iter()
+        .filter(|b| included(b.a) || included(b.b))
+        .collect();
+    let (point_count, bridge_count) = (points.len(), bridges.len());
+    let (points, bridges) = (take(points, limit), take(bridges, limit));
+    let system = |idx: usize| &starmap.systems[idx];
+
+    if format == OutputFormat::Json {
+        let report = json!({
+            "articulation_points": points.iter().map(|p| json!({
+                "system": system_json(system(p.system)),
+                "cut_off": p.cut_off,
+                "pieces": p.pieces,
+            })).collect::<Vec<_>>(),
+            "bridges": bridges.iter().map(|b| json!({
+                "from": system_json(system(b.a)),
+                "to": system_json(system(b.b)),
+                "cut_off": b.cut_off,
+            })).collect::<Vec<_>>(),
+            "total_articulation_points": point_count,
+            "total_bridges": bridge_count,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!(
+        "Articulation points: {}{}",
+        point_count,
+        shown(points.len(), point_count)
+    );
+    for (i, p) in points.iter().enumerate() {
+        println!(
+            "{:>4}. {}  cuts off {} systems ({} pieces)",
+            i + 1,
+            system_text(system(p.system)),
+            p.cut_off,
+            p.pieces
+        );
+    }
+    println!(
+        "Bridges: {}{}",
+        bridge_count,
+        shown(bridges.len(), bridge_count)
+    );
+    for (i, b) in bridges.iter().enumerate() {
+        println!(
+            "{:>4}. {} <-> {}  cuts off {} systems",
+            i + 1,
+            system_text(system(b.a)),
+            system_text(system(b.b)),
+            b.cut_off
+        );
+    }
+    Ok(())
+}
+
+fn shown(shown: usize, total: usize) -> String {
+    if shown < total {
+        format!(" (showing {})", shown)
+    } else {
+        String::new()
+    }
+}
+
+struct HubOptions {
+    by: Metric,
+    top: usize,
+    per_region: bool,
+    region: Option<String>,
+    config: CentralityConfig,
+    format: OutputFormat,
+}
+
+fn hubs(starmap: &Starmap, options: HubOptions) -> Result<()> {
+    let selected: Option<HashSet<usize>> = match &options.region {
+        Some(region) => Some(
+            region_systems(&starmap.systems, region)?
+                .into_iter()
+                .collect(),
+        ),
+        None => None,
+    };
+    let scores = evefrontier_lib::compute_centrality(&starmap.graph, &options.config)?;
+    let ranked: Vec<Centrality> = rank_by(&scores, options.by.into())
+        .into_iter()
+        .filter(|c| selected.as_ref().is_none_or(|s| s.contains(&c.system)))
+        .collect();
+
+    // Each group keeps the overall ranking order; ungrouped output is a single unnamed group.
+    let mut groups: BTreeMap<String, Vec<Centrality>> = BTreeMap::new();
+    for c in ranked {
+        let key = if options.per_region {
+            region_label(&starmap.systems[c.system]).unwrap_or_else(|| "-".to_string())
+        } else {
+            String::new()
+        };
+        let group = groups.entry(key).or_default();
+        if group.len() < options.top {
+            group.push(c);
+        }
+    }
+
+    let sampled = options
+        .config
+        .samples
+        .filter(|&k| k < starmap.systems.len());
+    if options.format == OutputFormat::Json {
+        let hub_json = |hubs: &[Centrality]| -> Vec<Value> {
+            hubs.iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    let mut hub = system_json(&starmap.systems[c.system]);
+                    hub["rank"] = json!(i + 1);
+                    hub["degree"] = json!(c.degree);
+                    hub["betweenness"] = json!(c.betweenness);
+                    hub["closeness"] = json!(c.closeness);
+                    hub
+                })
+                .collect()
+        };
+        let mut report = json!({ "metric": options.by.name(), "samples": sampled });
+        if options.per_region {
+            report["regions"] = groups
+                .iter()
+                .map(|(region, hubs)| json!({ "region": region, "hubs": hub_json(hubs) }))
+                .collect();
+        } else {
+            report["hubs"] = json!(groups
+                .values()
+                .next()
+                .map(|h| hub_json(h))
+                .unwrap_or_default());
+        }
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    match sampled {
+        Some(k) => println!(
+            "Ranked by {} (estimated from {} of {} systems)",
+            options.by.name(),
+            k,
+            starmap.systems.len()
+        ),
+        None => println!("Ranked by {}", options.by.name()),
+    }
+    for (region, hubs) in &groups {
+        let indent = if options.per_region {
+            println!("{}:", region);
+            "  "
+        } else {
+            ""
+        };
+        for (i, c) in hubs.iter().enumerate() {
+            println!(
+                "{}{:>4}. {}  degree {}  betweenness {:.4}  closeness {:.4}",
+                indent,
+                i + 1,
+                system_text(&starmap.systems[c.system]),
+                c.degree,
+                c.betweenness,
+                c.closeness
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use evefrontier_lib::{CacheEntry, PrunePolicy};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// List cached release assets, most recently used first
+    List,
+    /// Show details of one cached asset
+    Info { name: String },
+    /// Remove cached assets by age and/or count
+    Prune {
+        /// Remove assets last used longer ago than this (e.g. 30d, 12h, 90m)
+        #[arg(long, value_name = "AGE", value_parser = parse_age)]
+        older_than: Option<Duration>,
+        /// Keep only the N most recently used assets
+        #[arg(long, value_name = "N")]
+        keep: Option<usize>,
+    },
+    /// Remove every cached asset
+    Clear,
+}
+
+/// Parse `<number><unit>` where the unit is one of `s`, `m`, `h`, `d` or `w`.
+fn parse_age(s: &str) -> Result<Duration> {
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .with_context(|| format!("missing unit in '{}' (use s, m, h, d or w)", s))?;
+    let (value, unit) = s.split_at(split);
+    let value: u64 = value
+        .parse()
+        .with_context(|| format!("invalid number in '{}'", s))?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => bail!("unknown unit '{}' (use s, m, h, d or w)", unit),
+    };
+    let total = value
+        .checked_mul(secs)
+        .with_context(|| format!("duration '{}' is too large", s))?;
+    Ok(Duration::from_secs(total))
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn format_age(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs();
+    match secs {
+        s if s < 60 => "just now".to_string(),
+        s if s < 60 * 60 => format!("{}m ago", s / 60),
+        s if s < 24 * 60 * 60 => format!("{}h ago", s / (60 * 60)),
+        s => format!("{}d ago", s / (24 * 60 * 60)),
+    }
+}
+
+fn print_summary(entry: &CacheEntry) {
+    println!(
+        "{}  {}  {}  {}  {}",
+        entry.name,
+        entry.release_tag.as_deref().unwrap_or("-"),
+        format_size(entry.size),
+        &entry.sha256[..12.min(entry.sha256.len())],
+        format_age(entry.last_used),
+    );
+}
+
+pub fn run(command: CacheCommand, data_path: &Path) -> Result<()> {
+    let root = evefrontier_lib::default_cache_root()?;
+    // The installed DB is a copy outside the cache; keep the asset it came from, and the DB
+    // itself for a data path that points into the cache.
+    let in_use = evefrontier_lib::installed_asset(&root, data_path)?;
+    let mut protected = vec![data_path];
+    protected.extend(in_use.as_deref());
+
+    match command {
+        CacheCommand::List => {
+            let entries = evefrontier_lib::list_cache(&root)?;
+            if entries.is_empty() {
+                println!("Cache is empty: {}", root.display());
+            }
+            for entry in &entries {
+                print_summary(entry);
+            }
+        }
+        CacheCommand::Info { name } => {
+            let entry = evefrontier_lib::cache_entry(&root, &name)?;
+            println!("Name:        {}", entry.name);
+            println!("Path:        {}", entry.path.display());
+            println!(
+                "Release:     {}",
+                entry.release_tag.as_deref().unwrap_or("unknown")
+            );
+            println!(
+                "Source:      {}",
+                entry.source.as_deref().unwrap_or("unknown")
+            );
+            println!("Size:        {}", format_size(entry.size));
+            println!("SHA-256:     {}", entry.sha256);
+            println!("Last used:   {}", format_age(entry.last_used));
+            for file in &entry.extracted {
+                println!("Extracted:   {}", file.display());
+            }
+            if protected.iter().any(|p| entry.contains(p)) {
+                println!("In use:      yes");
+            }
+        }
+        CacheCommand::Prune { older_than, keep } => {
+            if older_than.is_none() && keep.is_none() {
+                bail!("prune needs --older-than and/or --keep");
+            }
+            let policy = PrunePolicy { older_than, keep };
+            let removed = evefrontier_lib::prune_cache(&root, &policy, &protected)?;
+            report_removed(&removed);
+        }
+        CacheCommand::Clear => {
+            let removed = evefrontier_lib::clear_cache(&root, &protected)?;
+            report_removed(&removed);
+        }
+    }
+
+    Ok(())
+}
+
+fn report_removed(removed: &[CacheEntry]) {
+    let freed: u64 = removed.iter().map(|e| e.size).sum();
+    for entry in removed {
+        println!("Removed {}", entry.name);
+    }
+    println!(
+        "Removed {} asset(s), freed {}",
+        removed.len(),
+        format_size(freed)
+    );
+}
@@ -0,0 +1,79 @@
+use anyhow::Result;
+use evefrontier_lib::diff::{Membership, SystemRef};
+use evefrontier_lib::StarmapDiff;
+use std::path::Path;
+
+use crate::{LoadOptions, OutputFormat};
+
+pub fn run(old: &Path, new: &Path, format: OutputFormat, load: &LoadOptions) -> Result<()> {
+    let old = crate::load_starmap(old, load)?;
+    let new = crate::load_starmap(new, load)?;
+    let diff = evefrontier_lib::diff_starmaps(&old, &new);
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+        OutputFormat::Text => print_text(&diff),
+    }
+    Ok(())
+}
+
+fn system(s: &SystemRef) -> String {
+    format!("{} (id={})", s.name, s.id)
+}
+
+fn membership(m: &Membership) -> String {
+    let region = m.region.clone().or(m.region_id.map(|id| id.to_string()));
+    let constellation = m
+        .constellation
+        .clone()
+        .or(m.constellation_id.map(|id| id.to_string()));
+    format!(
+        "region {}, constellation {}",
+        region.as_deref().unwrap_or("-"),
+        constellation.as_deref().unwrap_or("-")
+    )
+}
+
+fn print_text(diff: &StarmapDiff) {
+    if diff.is_empty() {
+        println!("No differences");
+        return;
+    }
+
+    println!(
+        "Systems: {} added, {} removed, {} renamed",
+        diff.added_systems.len(),
+        diff.removed_systems.len(),
+        diff.renamed_systems.len()
+    );
+    for s in &diff.added_systems {
+        println!("+ {}", system(s));
+    }
+    for s in &diff.removed_systems {
+        println!("- {}", system(s));
+    }
+    for r in &diff.renamed_systems {
+        println!("~ {} -> {} (id={})", r.old_name, r.new_name, r.id);
+    }
+
+    println!(
+        "Jumps: {} added, {} removed",
+        diff.added_jumps.len(),
+        diff.removed_jumps.len()
+    );
+    for j in &diff.added_jumps {
+        println!("+ {} <-> {}", system(&j.from), system(&j.to));
+    }
+    for j in &diff.removed_jumps {
+        println!("- {} <-> {}", system(&j.from), system(&j.to));
+    }
+
+    println!("Membership: {} changed", diff.membership_changes.len());
+    for c in &diff.membership_changes {
+        println!(
+            "~ {}: {} -> {}",
+            system(&c.system),
+            membership(&c.old),
+            membership(&c.new)
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::{bail, Context, Result};
+use clap::{Args, ValueEnum};
+use evefrontier_lib::{ExportFormat, Starmap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Dot,
+    Graphml,
+    Gexf,
+}
+
+impl From<Format> for ExportFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Dot => ExportFormat::Dot,
+            Format::Graphml => ExportFormat::GraphMl,
+            Format::Gexf => ExportFormat::Gexf,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Output format; defaults to the extension of --output, or DOT
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+    /// Write to this file instead of stdout
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+    /// Only export systems in this region (name or id)
+    #[arg(long, conflicts_with = "around")]
+    region: Option<String>,
+    /// Only export systems within --jumps jumps of this system
+    #[arg(long, value_name = "SYSTEM")]
+    around: Option<String>,
+    /// Neighbourhood radius for --around, in jumps
+    #[arg(long, default_value_t = 1, requires = "around")]
+    jumps: usize,
+}
+
+pub fn run(args: ExportArgs, starmap: &Starmap) -> Result<()> {
+    let format = match (args.format, &args.output) {
+        (Some(format), _) => format.into(),
+        (None, Some(path)) => {
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            match ExportFormat::from_extension(ext) {
+                Some(format) => format,
+                None => bail!(
+                    "cannot tell the format of {}; pass --format",
+                    path.display()
+                ),
+            }
+        }
+        (None, None) => ExportFormat::Dot,
+    };
+
+    let nodes = if let Some(region) = &args.region {
+        Some(evefrontier_lib::export::region_systems(
+            &starmap.systems,
+            region,
+        )?)
+    } else if let Some(center) = &args.around {
+        let start = evefrontier_lib::find_system(&starmap.systems, center)?;
+        Some(evefrontier_lib::export::neighbourhood(
+            &starmap.graph,
+            start,
+            args.jumps,
+        ))
+    } else {
+        None
+    };
+
+    let mut writer: Box<dyn Write> = match &args.output {
+        Some(path) => {
+            Box::new(BufWriter::new(File::create(path).with_context(|| {
+                format!("failed to create {}", path.display())
+            })?))
+        }
+        None => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+    evefrontier_lib::export_starmap(&mut writer, starmap, nodes.as_deref(), format)?;
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use anyhow::Result;
+use clap::Subcommand;
+use evefrontier_lib::components::summarize_components;
+use evefrontier_lib::Starmap;
+
+use crate::OutputFormat;
+
+#[derive(Subcommand)]
+pub enum GraphCommand {
+    /// Summarize the connected components of the gate network
+    Components {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+        /// List every system of each component instead of a few examples
+        #[arg(long)]
+        systems: bool,
+    },
+}
+
+/// Systems shown per component in text output without `--systems`.
+pub(crate) const EXAMPLES: usize = 5;
+
+pub fn run(command: GraphCommand, starmap: &Starmap) -> Result<()> {
+    match command {
+        GraphCommand::Components { format, systems } => components(starmap, format, systems),
+    }
+}
+
+fn components(starmap: &Starmap, format: OutputFormat, all_systems: bool) -> Result<()> {
+    let reports = summarize_components(starmap);
+
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} systems in {} connected component{}",
+        starmap.systems.len(),
+        reports.len(),
+        if reports.len() == 1 { "" } else { "s" }
+    );
+    for (i, report) in reports.iter().enumerate() {
+        let regions = if report.regions.is_empty() {
+            String::new()
+        } else {
+            let count = report.regions.len();
+            format!(" in {} region{}", count, if count == 1 { "" } else { "s" })
+        };
+        println!("{}. {} systems{}", i + 1, report.size, regions);
+        if report.regions.len() <= EXAMPLES && !report.regions.is_empty() {
+            println!("   regions: {}", report.regions.join(", "));
+        }
+        let shown = if all_systems {
+            report.systems.len()
+        } else {
+            EXAMPLES
+        };
+        println!("   systems: {}", name_list(&report.systems, shown));
+    }
+    Ok(())
+}
+
+/// The first `shown` of `names` joined by commas, and how many more there are.
+pub(crate) fn name_list<S: AsRef<str>>(names: &[S], shown: usize) -> String {
+    let mut list = names[..shown.min(names.len())]
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>()
+        .join(", ");
+    if shown < names.len() {
+        list.push_str(&format!(", ... ({} more)", names.len() - shown));
+    }
+    list
+}
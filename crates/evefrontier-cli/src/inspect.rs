@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+use evefrontier_lib::inspect::DetectedSchema;
+use evefrontier_lib::{DatasetInspection, SchemaMapping};
+use std::path::Path;
+
+use crate::OutputFormat;
+
+pub fn run(path: &Path, format: OutputFormat, mapping: &SchemaMapping) -> Result<()> {
+    let inspection = evefrontier_lib::inspect_dataset_with(path, mapping)
+        .with_context(|| format!("failed to inspect {}", path.display()))?;
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&inspection)?),
+        OutputFormat::Text => print_text(path, &inspection),
+    }
+    Ok(())
+}
+
+fn schema_details(schema: &DetectedSchema) -> Vec<String> {
+    let mut details = Vec::new();
+    if let Some(column) = &schema.region_column {
+        let names = if schema.region_names {
+            "with names"
+        } else {
+            "ids only"
+        };
+        details.push(format!("regions from {} ({})", column, names));
+    }
+    if let Some(column) = &schema.constellation_column {
+        let names = if schema.constellation_names {
+            "with names"
+        } else {
+            "ids only"
+        };
+        details.push(format!("constellations from {} ({})", column, names));
+    }
+    if let Some(columns) = &schema.coordinate_columns {
+        details.push(format!("coordinates from {}", columns.join("/")));
+    }
+    details
+}
+
+fn print_text(path: &Path, inspection: &DatasetInspection) {
+    let schema = &inspection.schema;
+    println!("Dataset: {}", path.display());
+    println!("Schema: {}", schema.variant.as_str());
+    if let (Some(systems), Some(jumps)) = (&schema.systems_table, &schema.jumps_table) {
+        println!("  systems from {}, jumps from {}", systems, jumps);
+    }
+    for detail in schema_details(schema) {
+        println!("  {}", detail);
+    }
+    for problem in &schema.problems {
+        println!("  ! {}", problem);
+    }
+
+    println!("Tables: {}", inspection.tables.len());
+    for table in &inspection.tables {
+        let rows = table
+            .rows
+            .map_or_else(|| "rows unknown".to_string(), |n| format!("{} rows", n));
+        println!("{} ({}, {})", table.name, table.kind, rows);
+        for column in &table.columns {
+            let mut line = format!("  {}", column.name);
+            if let Some(ty) = &column.declared_type {
+                line.push(' ');
+                line.push_str(ty);
+            }
+            if column.primary_key {
+                line.push_str(" PRIMARY KEY");
+            }
+            if column.not_null {
+                line.push_str(" NOT NULL");
+            }
+            if let Some(default) = &column.default {
+                line.push_str(&format!(" DEFAULT {}", default));
+            }
+            println!("{}", line);
+        }
+    }
+}
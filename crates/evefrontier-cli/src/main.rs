@@ -1,13 +1,51 @@
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use anyhow::{bail, Context};
+use clap::{Parser, Subcommand, ValueEnum};
+use evefrontier_lib::{
+    Config, DatasetSelector, DownloadOptions, ErrorKind, NamePattern, SchemaMapping, SourceFormat,
+    Starmap,
+};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+mod analyze;
+mod cache;
+mod diff;
+mod export;
+mod graph;
+mod inspect;
+mod near;
+mod nearby;
+mod progress;
+mod route;
+mod stats;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// Path to data DB file (overrides env var and default)
+    /// Path to the star map: a SQLite DB, a JSON file, or CSV (overrides env var and default)
     #[arg(long)]
     data_dir: Option<PathBuf>,
 
+    /// Format of star map files; guessed from the extension when omitted
+    #[arg(long, value_enum, global = true)]
+    source_format: Option<SourceFormatArg>,
+
+    /// Named dataset profile; each profile keeps its own DB under the default data directory
+    #[arg(long, global = true, env = "EVEFRONTIER_PROFILE")]
+    profile: Option<String>,
+
+    /// Suppress progress output
+    #[arg(long, short, global = true)]
+    quiet: bool,
+
+    /// Always read the star map from the database instead of its binary snapshot
+    #[arg(long, global = true)]
+    no_snapshot: bool,
+
+    /// TOML config of schema mappings and ships; defaults to config.toml in the config directory
+    #[arg(long, global = true, env = "EVEFRONTIER_CONFIG", value_name = "PATH")]
+    config: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -16,52 +54,287 @@ struct Cli {
 enum Commands {
     /// Download or ensure the dataset
     Download {
+        /// Download the release asset again and reinstall it, even when a DB is already installed
         #[arg(long)]
         force: bool,
+        /// Pick the release asset whose name matches this pattern
+        #[arg(long, value_name = "PATTERN")]
+        asset: Option<String>,
+        /// Pick the `.db` entry inside a zip asset whose name matches this pattern
+        #[arg(long, value_name = "PATTERN")]
+        entry: Option<String>,
+        /// Treat --asset and --entry as regular expressions instead of globs
+        #[arg(long)]
+        regex: bool,
+        /// List the assets of the latest release instead of downloading
+        #[arg(long, conflicts_with = "from")]
+        list_assets: bool,
+        /// Install from a local file or URL (.db, .zip, .tar, .tar.gz or .db.gz) instead of
+        /// the latest GitHub release; replaces the DB at the data path
+        #[arg(long, value_name = "PATH_OR_URL", conflicts_with = "asset")]
+        from: Option<String>,
+    },
+    /// Inspect and prune the downloaded-asset cache
+    Cache {
+        #[command(subcommand)]
+        command: cache::CacheCommand,
+    },
+    /// Compute a tour starting at START, or the shortest route from START to TO
+    Route(route::RouteArgs),
+    /// List the systems within N jumps, or D light-years, of SYSTEM, grouped by distance
+    Nearby(nearby::NearbyArgs),
+    /// List the systems closest to SYSTEM or --point in a straight line
+    Near(near::NearArgs),
+    /// Report systems, jumps and region/constellation membership that changed between two DBs
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Export the star map as Graphviz DOT, GraphML or GEXF
+    Export(export::ExportArgs),
+
+    /// Rank systems and gates by their strategic importance
+    Analyze {
+        #[command(subcommand)]
+        command: analyze::AnalyzeCommand,
+    },
+
+    /// Analyse the structure of the gate network
+    Graph {
+        #[command(subcommand)]
+        command: graph::GraphCommand,
+    },
+
+    /// Count systems, jumps and components and measure degrees and distances, overall and per
+    /// region
+    Stats {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
     },
-    /// Compute route starting at SYSTEM_NAME
-    Route { start: String },
+
+    /// List the tables, columns and row counts of the dataset and the schema the loader detects
+    Inspect {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SourceFormatArg {
+    Sqlite,
+    Json,
+    Csv,
+}
+
+impl From<SourceFormatArg> for SourceFormat {
+    fn from(format: SourceFormatArg) -> Self {
+        match format {
+            SourceFormatArg::Sqlite => SourceFormat::Sqlite,
+            SourceFormatArg::Json => SourceFormat::Json,
+            SourceFormatArg::Csv => SourceFormat::Csv,
+        }
+    }
+}
+
+/// How star map files are read, from the global options and the config file.
+#[derive(Clone)]
+pub(crate) struct LoadOptions {
+    format: Option<SourceFormat>,
+    no_snapshot: bool,
+    mapping: SchemaMapping,
 }
 
-fn resolve_data_path(cli: Option<PathBuf>) -> PathBuf {
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    Text,
+    Json,
+}
+
+fn resolve_data_path(cli: Option<PathBuf>, profile: Option<&str>) -> PathBuf {
     if let Some(p) = cli {
         return p;
     }
     if let Ok(env) = std::env::var("EVEFRONTIER_DATA_DIR") {
         return PathBuf::from(env);
     }
-    if let Some(proj) = directories::ProjectDirs::from("com", "evefrontier", "evefrontier") {
-        return proj.data_dir().join("static_data.db");
+    let data_dir = match directories::ProjectDirs::from("com", "evefrontier", "evefrontier") {
+        Some(proj) => proj.data_dir().to_path_buf(),
+        None => dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".local")
+            .join("evefrontier"),
+    };
+    match profile {
+        Some(name) => data_dir.join("profiles").join(name).join("static_data.db"),
+        None => data_dir.join("static_data.db"),
+    }
+}
+
+/// The config file given with `--config`, or the default one when it exists.
+fn load_config(path: Option<&Path>) -> anyhow::Result<Config> {
+    if let Some(path) = path {
+        return Ok(Config::load(path)?);
+    }
+    let default = directories::ProjectDirs::from("com", "evefrontier", "evefrontier")
+        .map(|proj| proj.config_dir().join("config.toml"));
+    match default {
+        Some(path) if path.is_file() => Ok(Config::load(&path)?),
+        _ => Ok(Config::default()),
+    }
+}
+
+fn validate_profile(name: &str) -> anyhow::Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && name != "."
+        && name != "..";
+    if !valid {
+        bail!(
+            "invalid profile name '{}': use letters, digits, '-', '_' or '.'",
+            name
+        );
+    }
+    Ok(())
+}
+
+fn parse_pattern(
+    pattern: Option<&str>,
+    regex: bool,
+) -> evefrontier_lib::Result<Option<NamePattern>> {
+    pattern
+        .map(|p| {
+            if regex {
+                NamePattern::regex(p)
+            } else {
+                NamePattern::glob(p)
+            }
+        })
+        .transpose()
+}
+
+/// Load the star map at `path`, through its snapshot unless `--no-snapshot` was given.
+pub(crate) fn load_starmap(path: &Path, options: &LoadOptions) -> anyhow::Result<Starmap> {
+    let starmap = if options.no_snapshot {
+        let source = evefrontier_lib::open_source_with(path, options.format, &options.mapping);
+        Starmap::from_source(source.as_ref())
+    } else {
+        Starmap::open_with(path, options.format, &options.mapping)
+    };
+    starmap.with_context(|| format!("failed to load {}", path.display()))
+}
+
+/// Exit status for a failed run: each library error category gets its own code so scripts can
+/// tell a network failure from a typo in a system name.
+fn exit_code(err: &anyhow::Error) -> u8 {
+    let kind = err
+        .chain()
+        .find_map(|e| e.downcast_ref::<evefrontier_lib::Error>())
+        .map(evefrontier_lib::Error::kind);
+    match kind {
+        Some(ErrorKind::InvalidInput) => 2,
+        Some(ErrorKind::Download) => 3,
+        Some(ErrorKind::Extract) => 4,
+        Some(ErrorKind::Schema) => 5,
+        Some(ErrorKind::Database) => 6,
+        Some(ErrorKind::UnknownSystem) => 7,
+        Some(ErrorKind::NoRoute) => 8,
+        Some(ErrorKind::Io) => 9,
+        _ => 1,
     }
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".local")
-        .join("evefrontier")
-        .join("static_data.db")
 }
 
-fn main() -> anyhow::Result<()> {
+fn main() -> ExitCode {
     let cli = Cli::parse();
-    let data_path = resolve_data_path(cli.data_dir.clone());
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {:#}", err);
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
+
+fn run(cli: Cli) -> anyhow::Result<()> {
+    if let Some(profile) = &cli.profile {
+        validate_profile(profile)?;
+    }
+    let data_path = resolve_data_path(cli.data_dir.clone(), cli.profile.as_deref());
+    let progress = progress::reporter(cli.quiet);
+    let config = load_config(cli.config.as_deref())?;
+    let load = LoadOptions {
+        format: cli.source_format.map(SourceFormat::from),
+        no_snapshot: cli.no_snapshot,
+        mapping: config.schema.clone(),
+    };
 
     match cli.command {
-        Commands::Download { force: _ } => {
-            let db = evefrontier_lib::ensure_c3e6_dataset(Some(&data_path))?;
+        Commands::Download {
+            force,
+            asset,
+            entry,
+            regex,
+            list_assets,
+            from,
+        } => {
+            let selector = DatasetSelector {
+                asset: parse_pattern(asset.as_deref(), regex)?,
+                entry: parse_pattern(entry.as_deref(), regex)?,
+            };
+            if list_assets {
+                let release = evefrontier_lib::latest_release()?;
+                println!("Release {}", release.tag);
+                for a in &release.assets {
+                    let marker = match &selector.asset {
+                        Some(p) if p.matches(&a.name) => "*",
+                        _ => " ",
+                    };
+                    println!("{} {} ({} bytes)", marker, a.name, a.size);
+                }
+                return Ok(());
+            }
+            let options = DownloadOptions {
+                schema: config.schema.clone(),
+                force,
+                ..DownloadOptions::default()
+            };
+            if let Some(source) = from {
+                let db = evefrontier_lib::install_from(
+                    &source,
+                    &data_path,
+                    &selector,
+                    &options,
+                    progress.as_ref(),
+                )?;
+                println!("Installed {} from {}", db.display(), source);
+                return Ok(());
+            }
+            let db = evefrontier_lib::ensure_dataset_with(
+                Some(&data_path),
+                &selector,
+                &options,
+                progress.as_ref(),
+            )?;
             println!("Downloaded/Using: {}", db.display());
         }
-        Commands::Route { start } => {
-            let (systems, jumps) = evefrontier_lib::load_starmap(&data_path)?;
-            let graph = evefrontier_lib::build_graph(&systems, &jumps);
-            let start_idx = systems
-                .iter()
-                .position(|s| s.name == start)
-                .ok_or_else(|| anyhow::anyhow!("Start system '{}' not found", start))?;
-            let route = evefrontier_lib::optimal_route(&graph, start_idx);
-            for idx in route {
-                let s = &systems[idx];
-                println!("{} (id={})", s.name, s.id);
-            }
+        Commands::Cache { command } => cache::run(command, &data_path)?,
+        Commands::Export(args) => export::run(args, &load_starmap(&data_path, &load)?)?,
+        Commands::Diff { old, new, format } => diff::run(&old, &new, format, &load)?,
+        Commands::Inspect { format } => inspect::run(&data_path, format, &load.mapping)?,
+        Commands::Route(args) => {
+            let ship = args.ship.as_deref().map(|n| config.ship(n)).transpose()?;
+            let starmap = load_starmap(&data_path, &load)?;
+            route::run(args, &starmap, ship, progress.as_ref())?
         }
+        Commands::Analyze { command } => analyze::run(command, &load_starmap(&data_path, &load)?)?,
+        Commands::Near(args) => near::run(args, &load_starmap(&data_path, &load)?)?,
+        Commands::Nearby(args) => nearby::run(args, &load_starmap(&data_path, &load)?)?,
+        Commands::Stats { format } => stats::run(&load_starmap(&data_path, &load)?, format)?,
+        Commands::Graph { command } => graph::run(command, &load_starmap(&data_path, &load)?)?,
     }
 
     Ok(())
@@ -0,0 +1,94 @@
+use anyhow::{bail, Result};
+use clap::Args;
+use evefrontier_lib::{Position, SpatialIndex, Starmap};
+use serde_json::json;
+
+use crate::OutputFormat;
+
+#[derive(Args)]
+#[command(group = clap::ArgGroup::new("from").required(true).args(["system", "point"]))]
+pub struct NearArgs {
+    /// The system to measure from
+    system: Option<String>,
+    /// Measure from these coordinates instead, in metres as stored in the dataset
+    #[arg(long, value_name = "X,Y,Z", value_parser = parse_point, allow_hyphen_values = true)]
+    point: Option<Position>,
+    /// Show the K closest systems; with --radius, at most K of them
+    #[arg(long, value_name = "K")]
+    count: Option<usize>,
+    /// Show every system within R light-years
+    #[arg(long, value_name = "R")]
+    radius: Option<f64>,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+}
+
+const DEFAULT_COUNT: usize = 10;
+
+fn parse_point(value: &str) -> Result<Position, String> {
+    let parts: Vec<&str> = value.split(',').map(str::trim).collect();
+    let [x, y, z] = parts[..] else {
+        return Err("expected three comma-separated coordinates".to_string());
+    };
+    let coordinate = |s: &str| {
+        s.parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| format!("'{}' is not a number", s))
+    };
+    Ok(Position {
+        x: coordinate(x)?,
+        y: coordinate(y)?,
+        z: coordinate(z)?,
+    })
+}
+
+pub fn run(args: NearArgs, starmap: &Starmap) -> Result<()> {
+    let index = SpatialIndex::new(&starmap.systems);
+    if index.is_empty() {
+        bail!("the star map has no coordinates; straight-line queries need a dataset with coordinate columns");
+    }
+    let (point, exclude) = match (&args.system, args.point) {
+        (Some(name), _) => {
+            let idx = evefrontier_lib::find_system(&starmap.systems, name)?;
+            match starmap.systems[idx].position {
+                Some(position) => (position, Some(idx)),
+                None => bail!("{} has no coordinates", name),
+            }
+        }
+        (None, Some(point)) => (point, None),
+        (None, None) => unreachable!("clap requires a system or --point"),
+    };
+
+    // Ask for one more so that the query system itself can be dropped.
+    let extra = usize::from(exclude.is_some());
+    let mut found = match args.radius {
+        Some(radius) => index.within(point, radius)?,
+        None => index.nearest(point, args.count.unwrap_or(DEFAULT_COUNT) + extra),
+    };
+    found.retain(|n| Some(n.system) != exclude);
+    if let Some(count) = args.count {
+        found.truncate(count);
+    }
+
+    if args.format == OutputFormat::Json {
+        let systems: Vec<_> = found
+            .iter()
+            .map(|n| {
+                let s = &starmap.systems[n.system];
+                json!({ "id": s.id, "name": s.name, "region": s.region, "light_years": n.light_years })
+            })
+            .collect();
+        let report = json!({
+            "from": { "system": args.system, "x": point.x, "y": point.y, "z": point.z },
+            "systems": systems,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    for n in &found {
+        let s = &starmap.systems[n.system];
+        println!("{:>10.3} ly  {} (id={})", n.light_years, s.name, s.id);
+    }
+    Ok(())
+}
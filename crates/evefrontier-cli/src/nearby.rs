@@ -0,0 +1,120 @@
+use anyhow::Result;
+use clap::Args;
+use evefrontier_lib::{Nearby, Reach, Starmap, System};
+use serde_json::json;
+
+use crate::OutputFormat;
+
+#[derive(Args)]
+pub struct NearbyArgs {
+    system: String,
+    /// Systems at most N gate jumps away, in one ring per jump count
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 5,
+        conflicts_with = "light_years"
+    )]
+    jumps: usize,
+    /// Systems at most D light-years of gate travel away instead; needs coordinates
+    #[arg(long, value_name = "D")]
+    light_years: Option<f64>,
+    /// Width of each ring in light-years; defaults to a fifth of --light-years, or 1 when it is 0
+    #[arg(long, value_name = "W", requires = "light_years")]
+    ring_width: Option<f64>,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+}
+
+pub fn run(args: NearbyArgs, starmap: &Starmap) -> Result<()> {
+    let start = evefrontier_lib::find_system(&starmap.systems, &args.system)?;
+    let reach = match args.light_years {
+        Some(max) => Reach::LightYears {
+            max,
+            // A zero radius still needs a positive width for its single ring.
+            ring_width: args
+                .ring_width
+                .unwrap_or(if max > 0.0 { max / 5.0 } else { 1.0 }),
+        },
+        None => Reach::Jumps { max: args.jumps },
+    };
+    let found = evefrontier_lib::nearby(starmap, start, reach)?;
+    match args.format {
+        OutputFormat::Json => print_json(starmap, &found)?,
+        OutputFormat::Text => print_text(starmap, &found),
+    }
+    Ok(())
+}
+
+fn system_text(system: &System) -> String {
+    format!("{} (id={})", system.name, system.id)
+}
+
+fn print_json(starmap: &Starmap, found: &Nearby) -> Result<()> {
+    let rings: Vec<_> = found
+        .rings
+        .iter()
+        .map(|ring| {
+            let systems: Vec<_> = ring
+                .systems
+                .iter()
+                .map(|s| {
+                    let system = &starmap.systems[s.system];
+                    json!({
+                        "id": system.id,
+                        "name": system.name,
+                        "region": system.region,
+                        "jumps": s.jumps,
+                        "light_years": s.light_years,
+                    })
+                })
+                .collect();
+            json!({ "index": ring.index, "systems": systems })
+        })
+        .collect();
+    let start = &starmap.systems[found.start];
+    let report = json!({
+        "start": { "id": start.id, "name": start.name },
+        "reach": found.reach,
+        "count": found.count(),
+        "rings": rings,
+    });
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+fn print_text(starmap: &Starmap, found: &Nearby) {
+    let start = system_text(&starmap.systems[found.start]);
+    match found.reach {
+        Reach::Jumps { max } => println!(
+            "{} systems within {} jumps of {}",
+            found.count(),
+            max,
+            start
+        ),
+        Reach::LightYears { max, .. } => println!(
+            "{} systems within {} light-years of {}",
+            found.count(),
+            max,
+            start
+        ),
+    }
+    // Ring 0 is the start itself.
+    for ring in found.rings.iter().filter(|r| r.index > 0) {
+        match found.reach {
+            Reach::Jumps { .. } => println!("{} jumps:", ring.index),
+            Reach::LightYears { ring_width, .. } => println!(
+                "{:.1} to {:.1} ly:",
+                (ring.index - 1) as f64 * ring_width,
+                ring.index as f64 * ring_width
+            ),
+        }
+        for s in &ring.systems {
+            let system = system_text(&starmap.systems[s.system]);
+            match s.light_years {
+                Some(ly) => println!("  {}  {:.2} ly, {} jumps", system, ly, s.jumps),
+                None => println!("  {}", system),
+            }
+        }
+    }
+}
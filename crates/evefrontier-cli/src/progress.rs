@@ -0,0 +1,109 @@
+use evefrontier_lib::{LogProgress, ProgressEvent, ProgressReporter, SilentProgress};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+use std::sync::Mutex;
+
+/// Draws library progress as `indicatif` bars on stderr.
+#[derive(Default)]
+pub struct IndicatifProgress {
+    bar: Mutex<Option<ProgressBar>>,
+}
+
+impl IndicatifProgress {
+    fn start(&self, bar: ProgressBar) {
+        let mut slot = self.bar.lock().unwrap();
+        if let Some(old) = slot.take() {
+            old.finish_and_clear();
+        }
+        *slot = Some(bar);
+    }
+
+    fn finish(&self, message: &'static str) {
+        if let Some(bar) = self.bar.lock().unwrap().take() {
+            bar.finish_with_message(message);
+        }
+    }
+
+    fn println(&self, line: String) {
+        match self.bar.lock().unwrap().as_ref() {
+            Some(bar) => bar.println(line),
+            None => eprintln!("{}", line),
+        }
+    }
+}
+
+impl ProgressReporter for IndicatifProgress {
+    fn report(&self, event: &ProgressEvent<'_>) {
+        match event {
+            ProgressEvent::DownloadStarted { url, total_bytes } => {
+                eprintln!("Downloading {}", url);
+                let bar = match total_bytes {
+                    Some(total) => ProgressBar::new(*total).with_style(
+                        ProgressStyle::with_template(
+                            "[{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
+                        )
+                        .unwrap()
+                        .progress_chars("=>-"),
+                    ),
+                    None => ProgressBar::new_spinner().with_style(
+                        ProgressStyle::with_template("[{elapsed_precise}] {spinner} {bytes}")
+                            .unwrap(),
+                    ),
+                };
+                self.start(bar);
+            }
+            ProgressEvent::DownloadProgress { downloaded, .. } => {
+                if let Some(bar) = self.bar.lock().unwrap().as_ref() {
+                    bar.set_position(*downloaded);
+                }
+            }
+            ProgressEvent::DownloadFinished { .. } => self.finish("download complete"),
+            ProgressEvent::UsingCached { path } => {
+                self.println(format!("Using cached: {}", path.display()));
+            }
+            ProgressEvent::ExtractStarted { entry, dest } => {
+                let bar = ProgressBar::new_spinner().with_message(format!(
+                    "Extracting {} to {}",
+                    entry,
+                    dest.display()
+                ));
+                bar.enable_steady_tick(std::time::Duration::from_millis(120));
+                self.start(bar);
+            }
+            ProgressEvent::ExtractFinished { .. } => self.finish("extraction complete"),
+            ProgressEvent::RouteProgress { visited, total } => {
+                let mut slot = self.bar.lock().unwrap();
+                let bar = slot.get_or_insert_with(|| {
+                    ProgressBar::new(*total as u64).with_style(
+                        ProgressStyle::with_template(
+                            "[{elapsed_precise}] [{bar:40.green/blue}] {pos}/{len} systems",
+                        )
+                        .unwrap()
+                        .progress_chars("=>-"),
+                    )
+                });
+                bar.set_position(*visited as u64);
+            }
+            ProgressEvent::RouteFinished { .. } => {
+                if let Some(bar) = self.bar.lock().unwrap().take() {
+                    bar.finish_and_clear();
+                }
+            }
+        }
+    }
+}
+
+/// Choose how to report progress: nothing with `--quiet`, bars on an interactive stderr,
+/// and `log` records otherwise so redirected output stays line-oriented.
+pub fn reporter(quiet: bool) -> Box<dyn ProgressReporter> {
+    if quiet {
+        return Box::new(SilentProgress);
+    }
+    if std::io::stderr().is_terminal() {
+        return Box::new(IndicatifProgress::default());
+    }
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .target(env_logger::Target::Stderr)
+        .init();
+    Box::new(LogProgress)
+}
@@ -0,0 +1,368 @@
+use anyhow::Result;
+use clap::Args;
+use evefrontier_lib::components::{component_of, tour_components, unreachable_regions};
+use evefrontier_lib::{
+    Alternatives, AlternativesConfig, ErrorKind, HopKind, ProgressReporter, RefuelRoute,
+    ShipProfile, Shortfall, StarGraph, Starmap,
+};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use crate::graph::{name_list, EXAMPLES};
+
+#[derive(Args)]
+pub struct RouteArgs {
+    start: String,
+    to: Option<String>,
+    /// Tour every connected component, not only the one START is in
+    #[arg(long, conflicts_with = "to")]
+    each_component: bool,
+    /// Also allow jump-drive hops between systems at most LY light-years apart; each hop is
+    /// then labelled as a gate or a jump
+    #[arg(long, value_name = "LY", conflicts_with = "ship")]
+    jump_range: Option<f64>,
+    /// Route for a ship profile from the config file: jump-drive hops within its range, with
+    /// the fuel each one burns
+    #[arg(long, value_name = "NAME")]
+    pub(crate) ship: Option<String>,
+    /// Refuel points file, one system name or id per line: find the route to TO that burns the
+    /// least fuel, stopping to fill up where the tank would run short
+    #[arg(long, value_name = "FILE", requires_all = ["ship", "to"])]
+    refuel: Option<PathBuf>,
+    /// Show up to K routes to TO, shortest first, each one different enough from the others
+    #[arg(
+        long,
+        value_name = "K",
+        requires = "to",
+        conflicts_with = "refuel",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    alternatives: Option<u32>,
+    /// With --alternatives, how alike a route may be to an earlier one: the systems they share
+    /// over the systems on either, from 0 to 1
+    #[arg(
+        long,
+        value_name = "S",
+        requires = "alternatives",
+        value_parser = parse_similarity,
+        default_value_t = AlternativesConfig::default().max_similarity
+    )]
+    max_similarity: f64,
+}
+
+fn parse_similarity(value: &str) -> Result<f64, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|s| (0.0..=1.0).contains(s))
+        .ok_or_else(|| format!("'{}' is not a number from 0 to 1", value))
+}
+
+/// How to print each hop: plain, labelled as gate or jump, or with fuel for a ship.
+#[derive(Clone, Copy)]
+enum Hops<'a> {
+    Plain,
+    Labelled,
+    Fuel(&'a ShipProfile),
+}
+
+pub fn run(
+    args: RouteArgs,
+    starmap: &Starmap,
+    ship: Option<&ShipProfile>,
+    progress: &dyn ProgressReporter,
+) -> Result<()> {
+    let systems = &starmap.systems;
+    if let (Some(file), Some(ship), Some(to)) = (&args.refuel, ship, &args.to) {
+        let points = evefrontier_lib::load_refuel_points(file, systems)?;
+        let start = evefrontier_lib::find_system(systems, &args.start)?;
+        let goal = evefrontier_lib::find_system(systems, to)?;
+        let found =
+            evefrontier_lib::refuel_route(starmap, ship, &points, start, goal).map_err(|err| {
+                match err.kind() {
+                    ErrorKind::NoRoute => anyhow::Error::new(err).context(format!(
+                        "{} cannot reach {} from {} with a {:.1} fuel tank and {} refuel points",
+                        ship.name,
+                        to,
+                        args.start,
+                        ship.fuel_capacity,
+                        points.len()
+                    )),
+                    _ => err.into(),
+                }
+            })?;
+        print_refuel_route(starmap, ship, &found);
+        return Ok(());
+    }
+    let jump_graph;
+    let graph = match ship.map(ShipProfile::effective_range).or(args.jump_range) {
+        Some(light_years) => {
+            jump_graph = starmap.jump_graph(light_years)?;
+            &jump_graph
+        }
+        None => &starmap.graph,
+    };
+    let hops = match ship {
+        Some(ship) => Hops::Fuel(ship),
+        None if args.jump_range.is_some() => Hops::Labelled,
+        None => Hops::Plain,
+    };
+    if let Some(to) = &args.to {
+        let no_route =
+            |err: evefrontier_lib::Error| match no_route_reason(starmap, graph, &args.start, to) {
+                Some(reason) => anyhow::Error::new(err).context(reason),
+                None => err.into(),
+            };
+        match args.alternatives {
+            Some(count) => {
+                let start = evefrontier_lib::find_system(systems, &args.start)?;
+                let goal = evefrontier_lib::find_system(systems, to)?;
+                let config = AlternativesConfig {
+                    count: count as usize,
+                    max_similarity: args.max_similarity,
+                    ..AlternativesConfig::default()
+                };
+                let found = evefrontier_lib::alternative_routes(graph, start, goal, &config);
+                if found.routes.is_empty() {
+                    return Err(no_route(evefrontier_lib::Error::NoRoute {
+                        from: args.start.clone(),
+                        to: to.clone(),
+                    }));
+                }
+                print_alternatives(starmap, graph, &found, &config, hops);
+            }
+            None => match evefrontier_lib::route_between(systems, graph, &args.start, to) {
+                Err(err) if err.kind() == ErrorKind::NoRoute => return Err(no_route(err)),
+                route => print_route(starmap, graph, &route?, hops),
+            },
+        }
+        return Ok(());
+    }
+
+    let start = evefrontier_lib::find_system(systems, &args.start)?;
+    if args.each_component {
+        let tours = tour_components(graph, start, progress);
+        let count = tours.len();
+        for (i, tour) in tours.iter().enumerate() {
+            if count > 1 {
+                println!(
+                    "# Component {} of {} ({} systems)",
+                    i + 1,
+                    count,
+                    distinct(tour)
+                );
+            }
+            print_route(starmap, graph, tour, hops);
+        }
+        return Ok(());
+    }
+
+    let route = evefrontier_lib::optimal_route_with_progress(graph, start, progress);
+    print_route(starmap, graph, &route, hops);
+    report_unreachable(starmap, graph, start);
+    Ok(())
+}
+
+/// Print one system per line; unless `hops` is plain, every system after the first says how it
+/// was reached.
+fn print_route(starmap: &Starmap, graph: &StarGraph, route: &[usize], hops: Hops) {
+    let Some(&first) = route.first() else {
+        return;
+    };
+    let name = |idx: usize| {
+        let s = &starmap.systems[idx];
+        format!("{} (id={})", s.name, s.id)
+    };
+    println!("{}", name(first));
+    if let Hops::Plain = hops {
+        for &idx in &route[1..] {
+            println!("{}", name(idx));
+        }
+        return;
+    }
+    let route_hops = evefrontier_lib::route_hops(&starmap.systems, graph, route);
+    let fuel = match hops {
+        Hops::Fuel(ship) => Some((ship, ship.fuel_use(&route_hops))),
+        _ => None,
+    };
+    for (i, hop) in route_hops.iter().enumerate() {
+        let kind = match hop.kind {
+            HopKind::Gate => "gate",
+            HopKind::JumpDrive => "jump",
+        };
+        let mut line = format!("{}  {}", name(hop.to), kind);
+        if let Some(ly) = hop.light_years {
+            line.push_str(&format!(", {:.2} ly", ly));
+        }
+        if let Some((_, fuel)) = &fuel {
+            line.push_str(&format!(", {:.1} fuel", fuel.per_hop[i]));
+        }
+        println!("{}", line);
+    }
+    if let Some((ship, fuel)) = fuel {
+        println!(
+            "Fuel: {:.1} of {:.1} ({})",
+            fuel.total, fuel.capacity, ship.name
+        );
+        if fuel.exceeds_capacity() {
+            eprintln!(
+                "warning: the route burns more fuel than {} holds; it needs refuelling on the way",
+                ship.name
+            );
+        }
+    }
+}
+
+/// Print each alternative route with its length and the systems it has in common with the
+/// shortest one, and warn when fewer were found than asked for.
+fn print_alternatives(
+    starmap: &Starmap,
+    graph: &StarGraph,
+    found: &Alternatives,
+    config: &AlternativesConfig,
+    hops: Hops,
+) {
+    let routes = &found.routes;
+    for (i, alternative) in routes.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let shared = match alternative.shared.len() {
+            _ if i == 0 => String::new(),
+            0 => ", no systems shared with route 1".to_string(),
+            n => format!(
+                ", {} system{} shared with route 1 (similarity {:.2}): {}",
+                n,
+                if n == 1 { "" } else { "s" },
+                alternative.similarity,
+                alternative
+                    .shared
+                    .iter()
+                    .map(|&v| starmap.systems[v].name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        println!(
+            "# Route {} of {}: {} jump{}{}",
+            i + 1,
+            routes.len(),
+            alternative.jumps,
+            if alternative.jumps == 1 { "" } else { "s" },
+            shared
+        );
+        print_route(starmap, graph, &alternative.route, hops);
+    }
+    match found.shortfall {
+        Some(Shortfall::Exhausted) => eprintln!(
+            "warning: found {} of {} routes; the others are too similar to one already shown (--max-similarity {})",
+            routes.len(),
+            config.count,
+            config.max_similarity
+        ),
+        Some(Shortfall::BudgetSpent) => eprintln!(
+            "warning: found {} of {} routes; gave up after {} candidate routes without finding more different enough (--max-similarity {})",
+            routes.len(),
+            config.count,
+            config.count.saturating_mul(config.candidates_per_route),
+            config.max_similarity
+        ),
+        None => {}
+    }
+}
+
+fn print_refuel_route(starmap: &Starmap, ship: &ShipProfile, found: &RefuelRoute) {
+    let name = |idx: usize| {
+        let s = &starmap.systems[idx];
+        format!("{} (id={})", s.name, s.id)
+    };
+    let mut stops = found.stops.iter().peekable();
+    let mut print_stop = |position: usize| {
+        if let Some(stop) = stops.next_if(|s| s.position == position) {
+            println!(
+                "  refuel: {:.1} left, filled to {:.1}",
+                stop.fuel_left, ship.fuel_capacity
+            );
+        }
+    };
+    println!("{}", name(found.route[0]));
+    print_stop(0);
+    for (i, hop) in found.hops.iter().enumerate() {
+        let kind = match hop.kind {
+            HopKind::Gate => "gate",
+            HopKind::JumpDrive => "jump",
+        };
+        let distance = hop
+            .light_years
+            .map_or_else(String::new, |ly| format!(", {:.2} ly", ly));
+        println!(
+            "{}  {}{}, {:.1} fuel, {:.1} left",
+            name(hop.to),
+            kind,
+            distance,
+            found.fuel_per_hop[i],
+            found.fuel_left[i]
+        );
+        print_stop(i + 1);
+    }
+    println!(
+        "Fuel: {:.1} burned, {} refuel stop{} ({})",
+        found.total_fuel,
+        found.stops.len(),
+        if found.stops.len() == 1 { "" } else { "s" },
+        ship.name
+    );
+}
+
+fn distinct(tour: &[usize]) -> usize {
+    tour.iter().collect::<HashSet<_>>().len()
+}
+
+/// Explain a failed point-to-point route when the systems sit in different components.
+fn no_route_reason(starmap: &Starmap, graph: &StarGraph, from: &str, to: &str) -> Option<String> {
+    let from_idx = evefrontier_lib::find_system(&starmap.systems, from).ok()?;
+    let to_idx = evefrontier_lib::find_system(&starmap.systems, to).ok()?;
+    let component = component_of(graph, from_idx);
+    if component.binary_search(&to_idx).is_ok() {
+        return None;
+    }
+    Some(format!(
+        "{} and {} are in different connected components ({} and {} systems)",
+        from,
+        to,
+        component.len(),
+        component_of(graph, to_idx).len()
+    ))
+}
+
+/// Warn on stderr about systems a tour from `start` could not reach.
+fn report_unreachable(starmap: &Starmap, graph: &StarGraph, start: usize) {
+    let unreachable = evefrontier_lib::unreachable_from(graph, start);
+    if unreachable.is_empty() {
+        return;
+    }
+    let components = evefrontier_lib::connected_components(graph).len() - 1;
+    eprintln!(
+        "warning: {} systems in {} other component{} are unreachable from {}; use --each-component to tour them",
+        unreachable.len(),
+        components,
+        if components == 1 { "" } else { "s" },
+        starmap.systems[start].name
+    );
+    let names: Vec<&str> = unreachable
+        .iter()
+        .map(|&v| starmap.systems[v].name.as_str())
+        .collect();
+    eprintln!("  systems: {}", name_list(&names, EXAMPLES));
+    for region in unreachable_regions(&starmap.systems, &unreachable) {
+        let name = region.region.as_deref().unwrap_or("(no region)");
+        if region.is_entirely_unreachable() {
+            eprintln!("  {}: all {} systems", name, region.total);
+        } else {
+            eprintln!(
+                "  {}: {} of {} systems",
+                name, region.unreachable, region.total
+            );
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use evefrontier_lib::{GraphStats, Starmap};
+
+use crate::OutputFormat;
+
+pub fn run(starmap: &Starmap, format: OutputFormat) -> Result<()> {
+    let stats = evefrontier_lib::graph_stats(starmap);
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+        OutputFormat::Text => print_text(&stats),
+    }
+    Ok(())
+}
+
+fn print_text(stats: &GraphStats) {
+    println!("Systems: {}", stats.systems);
+    println!("Jumps: {}", stats.jumps);
+    println!(
+        "Components: {} (largest {} systems, {} isolated systems)",
+        stats.components, stats.largest_component, stats.isolated_systems
+    );
+    println!(
+        "Degree: min {}, max {}, mean {:.2}",
+        stats.degree.min, stats.degree.max, stats.degree.mean
+    );
+    for d in &stats.degree.distribution {
+        println!("  degree {:>3}: {} systems", d.degree, d.systems);
+    }
+    println!("Diameter: {} jumps", stats.diameter);
+    println!("Radius: {} jumps", stats.radius);
+    println!(
+        "Average shortest path: {:.2} jumps",
+        stats.average_path_length
+    );
+
+    println!("Regions: {}", stats.regions.len());
+    for r in &stats.regions {
+        println!(
+            "  {}: {} systems, {} internal jumps, {} border jumps, {} components, mean degree {:.2}",
+            r.region.as_deref().unwrap_or("-"),
+            r.systems,
+            r.internal_jumps,
+            r.border_jumps,
+            r.components,
+            r.average_degree
+        );
+    }
+}
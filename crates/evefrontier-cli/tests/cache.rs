@@ -0,0 +1,44 @@
+//! Parsing of the `cache prune --older-than` age.
+
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+
+/// Run `cache prune --older-than age`.
+fn prune(dir: &Path, age: &str) -> Output {
+    common::cli(dir)
+        .args(["cache", "prune", "--older-than", age])
+        .output()
+        .expect("run the CLI")
+}
+
+#[test]
+fn ages_need_a_number_and_a_unit_that_fit() {
+    let dir = common::temp_dir("cache", "ages");
+    for age in ["30d", "12h", "90m", "45s", "2w"] {
+        assert!(prune(&dir, age).status.success(), "{}", age);
+    }
+
+    let cases = [
+        ("30", "missing unit in '30'"),
+        ("d", "invalid number in 'd'"),
+        ("3y", "unknown unit 'y'"),
+        (
+            "99999999999999999w",
+            "duration '99999999999999999w' is too large",
+        ),
+        (
+            "99999999999999999999s",
+            "invalid number in '99999999999999999999s'",
+        ),
+    ];
+    for (age, message) in cases {
+        let output = prune(&dir, age);
+        assert_eq!(output.status.code(), Some(2), "{}", age);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains(message), "{}: {}", age, stderr);
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
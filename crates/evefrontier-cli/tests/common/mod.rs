@@ -0,0 +1,39 @@
+//! Running the CLI binary in a scratch home directory for the integration tests.
+// Each test crate uses only some of these.
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// An empty directory under the system temp dir, unique to `suite`, `name` and this process.
+pub fn temp_dir(suite: &str, name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "evefrontier-cli-{}-{}-{}",
+        suite,
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+pub fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../docs/fixtures/minimal_static_data.db")
+}
+
+/// The CLI with `--quiet` and its config, data and cache directories inside `dir`, unaffected
+/// by the environment of whoever runs the tests.
+pub fn cli(dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_evefrontier-cli"));
+    command
+        .arg("--quiet")
+        .env("HOME", dir)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("XDG_CACHE_HOME", dir.join("cache"))
+        .env("XDG_DATA_HOME", dir.join("data"))
+        .env_remove("EVEFRONTIER_DATA_DIR")
+        .env_remove("EVEFRONTIER_PROFILE")
+        .env_remove("EVEFRONTIER_CONFIG");
+    command
+}
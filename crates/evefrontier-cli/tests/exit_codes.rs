@@ -0,0 +1,106 @@
+//! The exit status of the CLI for each category of library error.
+
+mod common;
+
+use evefrontier_lib::rusqlite::Connection;
+use std::path::Path;
+
+/// Run the CLI with `args` and return its exit code.
+fn exit_code(dir: &Path, args: &[&str]) -> i32 {
+    let output = common::cli(dir)
+        .arg("--no-snapshot")
+        .args(args)
+        .output()
+        .expect("run the CLI");
+    output.status.code().expect("the CLI exits normally")
+}
+
+#[test]
+fn each_error_category_has_its_own_exit_code() {
+    let dir = common::temp_dir("exit", "codes");
+    let db = common::fixture();
+    let db = db.to_str().unwrap();
+
+    // Two systems and no jump between them.
+    let islands = dir.join("islands.json");
+    std::fs::write(
+        &islands,
+        r#"{ "systems": [ { "id": 1, "name": "Alpha" }, { "id": 2, "name": "Beta" } ],
+            "jumps": [] }"#,
+    )
+    .unwrap();
+    let islands = islands.to_str().unwrap();
+
+    let no_tables = dir.join("empty.db");
+    Connection::open(&no_tables)
+        .unwrap()
+        .execute_batch("CREATE TABLE Unrelated(id INTEGER);")
+        .unwrap();
+    let not_sqlite = dir.join("garbage.db");
+    std::fs::write(&not_sqlite, vec![b'x'; 4096]).unwrap();
+    let not_archive = dir.join("notes.txt");
+    std::fs::write(&not_archive, b"not a dataset").unwrap();
+    let missing = dir.join("missing.json");
+    let data = dir.join("static_data.db");
+
+    let cases: [(&str, Vec<&str>, i32); 11] = [
+        (
+            "success",
+            vec!["--data-dir", db, "route", "Y:170N", "BetaTest"],
+            0,
+        ),
+        ("anyhow error", vec!["--profile", "..", "stats"], 1),
+        (
+            "profile after the command",
+            vec!["stats", "--profile", ".."],
+            1,
+        ),
+        ("invalid input", vec!["download", "--asset", "["], 2),
+        (
+            "no samples",
+            vec!["--data-dir", db, "analyze", "hubs", "--samples", "0"],
+            2,
+        ),
+        (
+            "extract",
+            vec![
+                "--data-dir",
+                data.to_str().unwrap(),
+                "download",
+                "--from",
+                not_archive.to_str().unwrap(),
+            ],
+            4,
+        ),
+        (
+            "schema",
+            vec!["--data-dir", no_tables.to_str().unwrap(), "stats"],
+            5,
+        ),
+        (
+            "database",
+            vec!["--data-dir", not_sqlite.to_str().unwrap(), "stats"],
+            6,
+        ),
+        (
+            "unknown system",
+            vec!["--data-dir", db, "route", "Y:170N", "Nowhere"],
+            7,
+        ),
+        (
+            "no route",
+            vec!["--data-dir", islands, "route", "Alpha", "Beta"],
+            8,
+        ),
+        (
+            "io",
+            vec!["--data-dir", missing.to_str().unwrap(), "stats"],
+            9,
+        ),
+    ];
+    for (name, args, expected) in cases {
+        assert_eq!(exit_code(&dir, &args), expected, "{}", name);
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
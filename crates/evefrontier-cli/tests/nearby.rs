@@ -0,0 +1,49 @@
+//! `nearby --light-years`, and the ring width it picks when none is given.
+
+mod common;
+
+use std::process::Output;
+
+/// Alpha, Beta and Gamma on a line at 0, 10 and 30 light-years, gated in that order.
+const MAP: &str = r#"{
+  "systems": [
+    { "id": 1, "name": "Alpha", "position": { "x": 0.0, "y": 0.0, "z": 0.0 } },
+    { "id": 2, "name": "Beta", "position": { "x": 9.4607304725808e16, "y": 0.0, "z": 0.0 } },
+    { "id": 3, "name": "Gamma", "position": { "x": 2.83821914177424e17, "y": 0.0, "z": 0.0 } }
+  ],
+  "jumps": [{ "from_id": 1, "to_id": 2 }, { "from_id": 2, "to_id": 3 }]
+}"#;
+
+fn nearby(name: &str, args: &[&str]) -> Output {
+    let dir = common::temp_dir("nearby", name);
+    let path = dir.join("map.json");
+    std::fs::write(&path, MAP).unwrap();
+    let output = common::cli(&dir)
+        .args(["--no-snapshot", "--data-dir"])
+        .arg(&path)
+        .args(["nearby", "Alpha", "--light-years"])
+        .args(args)
+        .output()
+        .expect("run the CLI");
+    let _ = std::fs::remove_dir_all(&dir);
+    output
+}
+
+#[test]
+fn a_zero_radius_needs_no_ring_width() {
+    let output = nearby("zero", &["0"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Alpha"));
+    assert!(!stdout.contains("Beta"));
+
+    let output = nearby("default", &["15"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Beta") && !stdout.contains("Gamma"));
+
+    // An explicit zero width is still the caller's mistake.
+    let output = nearby("explicit", &["15", "--ring-width", "0"]);
+    assert_eq!(output.status.code(), Some(2));
+}
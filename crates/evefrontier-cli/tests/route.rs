@@ -0,0 +1,51 @@
+//! What `route` reports on stderr about the systems a tour cannot reach.
+
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+
+/// Run the CLI on the JSON star map `map`.
+fn run(dir: &Path, map: &str, args: &[&str]) -> Output {
+    let path = dir.join("map.json");
+    std::fs::write(&path, map).unwrap();
+    common::cli(dir)
+        .args(["--no-snapshot", "--data-dir"])
+        .arg(&path)
+        .args(args)
+        .output()
+        .expect("run the CLI")
+}
+
+/// Systems 1 to `count`, alternating between regions "Even" and "Odd", with one gate 1 - 2.
+fn islands(count: usize) -> String {
+    let systems: Vec<_> = (1..=count)
+        .map(|i| {
+            serde_json::json!({
+                "id": i,
+                "name": format!("Sys{}", i),
+                "region": if i % 2 == 0 { "Even" } else { "Odd" },
+            })
+        })
+        .collect();
+    serde_json::json!({ "systems": systems, "jumps": [{ "from_id": 1, "to_id": 2 }] }).to_string()
+}
+
+#[test]
+fn unreachable_systems_are_named_up_to_a_limit() {
+    let dir = common::temp_dir("route", "unreachable");
+
+    let output = run(&dir, &islands(9), &["route", "Sys1"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("7 systems in 7 other components are unreachable from Sys1"));
+    assert!(stderr.contains("  systems: Sys3, Sys4, Sys5, Sys6, Sys7, ... (2 more)\n"));
+    assert!(stderr.contains("  Even: 3 of 4 systems\n"));
+    assert!(stderr.contains("  Odd: 4 of 5 systems\n"));
+
+    let output = run(&dir, &islands(4), &["route", "Sys2"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("  systems: Sys3, Sys4\n"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
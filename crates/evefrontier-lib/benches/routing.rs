@@ -0,0 +1,88 @@
+//! Route and centrality timings on synthetic galaxies. Run with
+//! `cargo bench -p evefrontier-lib --features synthetic`; set `EVEFRONTIER_BENCH_SYSTEMS` to a
+//! comma-separated list of galaxy sizes to override the defaults.
+
+use evefrontier_lib::{
+    alternative_routes, compute_centrality, generate, optimal_route, shortest_path,
+    AlternativesConfig, CentralityConfig, GalaxyConfig,
+};
+use std::time::{Duration, Instant};
+
+const DEFAULT_SIZES: [usize; 3] = [200, 2_000, 20_000];
+
+/// Exact centrality runs a search from every system, so it grows with the square of the galaxy
+/// size and takes tens of seconds at 20,000 systems; the sampled variant is always timed.
+const EXACT_CENTRALITY_LIMIT: usize = 2_000;
+
+fn time<T>(label: &str, mut f: impl FnMut() -> T) {
+    let mut runs = 0u32;
+    let started = Instant::now();
+    while runs == 0 || (started.elapsed() < Duration::from_secs(2) && runs < 100) {
+        std::hint::black_box(f());
+        runs += 1;
+    }
+    println!(
+        "{:<40} {:>10.3} ms/iter ({} runs)",
+        label,
+        started.elapsed().as_secs_f64() * 1000.0 / runs as f64,
+        runs
+    );
+}
+
+fn main() {
+    let requested: Option<Vec<usize>> =
+        std::env::var("EVEFRONTIER_BENCH_SYSTEMS")
+            .ok()
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|s| s.trim().parse().expect("galaxy sizes must be integers"))
+                    .collect()
+            });
+    let exact_centrality_limit = if requested.is_some() {
+        usize::MAX
+    } else {
+        EXACT_CENTRALITY_LIMIT
+    };
+    let sizes = requested.unwrap_or_else(|| DEFAULT_SIZES.to_vec());
+
+    for systems in sizes {
+        let config = GalaxyConfig {
+            systems,
+            regions: (systems / 200).max(1),
+            constellations_per_region: 20,
+            ..GalaxyConfig::default()
+        };
+        let started = Instant::now();
+        let starmap = generate(&config);
+        println!(
+            "generated {} systems and {} jumps in {:.1} ms",
+            starmap.systems.len(),
+            starmap.jumps.len(),
+            started.elapsed().as_secs_f64() * 1000.0
+        );
+
+        let far = starmap.systems.len() - 1;
+        time(&format!("shortest_path/{}", systems), || {
+            shortest_path(&starmap.graph, 0, far)
+        });
+        time(&format!("alternative_routes_3/{}", systems), || {
+            alternative_routes(&starmap.graph, 0, far, &AlternativesConfig::default())
+        });
+        time(&format!("optimal_route/{}", systems), || {
+            optimal_route(&starmap.graph, 0)
+        });
+        if systems <= exact_centrality_limit {
+            time(&format!("centrality/{}", systems), || {
+                compute_centrality(&starmap.graph, &CentralityConfig::default()).unwrap()
+            });
+        }
+        let sampled = CentralityConfig {
+            samples: Some(500),
+            seed: 0,
+        };
+        time(&format!("centrality_sampled_500/{}", systems), || {
+            compute_centrality(&starmap.graph, &sampled).unwrap()
+        });
+    }
+}
@@ -0,0 +1,238 @@
+//! Alternative routes between two systems, so that a fleet does not always take the one
+//! shortest path a camp would wait on.
+//!
+//! Candidates come from Yen's algorithm, which lists the loopless paths in order of jumps. Most
+//! of them differ from a shorter one by a system or two, so a candidate is kept only when it is
+//! different enough from every route kept so far, measured as the Jaccard similarity of the
+//! systems between the endpoints.
+
+use crate::graph::{simple_neighbours, StarGraph};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlternativesConfig {
+    /// Routes to return, counting the shortest one.
+    pub count: usize,
+    /// The highest similarity to an earlier route a new one may have, from 0 (no system in
+    /// common) to 1 (keep every loopless path).
+    pub max_similarity: f64,
+    /// How many candidates Yen's algorithm may produce per route asked for before giving up.
+    pub candidates_per_route: usize,
+}
+
+impl Default for AlternativesConfig {
+    fn default() -> Self {
+        AlternativesConfig {
+            count: 3,
+            max_similarity: 0.7,
+            candidates_per_route: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alternative {
+    pub route: Vec<usize>,
+    pub jumps: usize,
+    /// Systems between the endpoints that the shortest route passes through too, in the order
+    /// this route visits them.
+    pub shared: Vec<usize>,
+    /// Jaccard similarity to the shortest route; 1 for the shortest route itself.
+    pub similarity: f64,
+}
+
+/// Why [`alternative_routes`] returned fewer routes than asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Shortfall {
+    /// Every loopless route was looked at; the rest are too alike to one already kept, or
+    /// `goal` cannot be reached at all.
+    Exhausted,
+    /// Yen's algorithm produced `count * candidates_per_route` candidates without finding
+    /// enough different ones; a larger budget might find more.
+    BudgetSpent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alternatives {
+    /// The routes kept, shortest first.
+    pub routes: Vec<Alternative>,
+    /// Why the search stopped short of `config.count` routes, or `None` when it did not.
+    pub shortfall: Option<Shortfall>,
+}
+
+/// Up to `config.count` routes from `start` to `goal`, shortest first, each at most
+/// `config.max_similarity` alike to every earlier one. No routes when `goal` cannot be reached;
+/// `shortfall` tells whether the graph has no more different enough routes or none turned up
+/// within the candidate budget.
+pub fn alternative_routes(
+    graph: &StarGraph,
+    start: usize,
+    goal: usize,
+    config: &AlternativesConfig,
+) -> Alternatives {
+    let budget = config.count.saturating_mul(config.candidates_per_route);
+    let mut paths = ShortestPaths::new(graph, start, goal);
+    let mut examined = 0;
+    let mut kept: Vec<(Vec<usize>, HashSet<usize>)> = Vec::new();
+    let shortfall = loop {
+        if kept.len() == config.count {
+            break None;
+        }
+        if examined == budget {
+            break Some(Shortfall::BudgetSpent);
+        }
+        let Some(route) = paths.next() else {
+            break Some(Shortfall::Exhausted);
+        };
+        examined += 1;
+        let inner = interior(&route);
+        if kept
+            .iter()
+            .all(|(_, other)| jaccard(&inner, other) <= config.max_similarity)
+        {
+            kept.push((route, inner));
+        }
+    };
+
+    let best = kept
+        .first()
+        .map(|(_, inner)| inner.clone())
+        .unwrap_or_default();
+    let routes = kept
+        .into_iter()
+        .map(|(route, inner)| Alternative {
+            jumps: route.len() - 1,
+            shared: inner_order(&route).filter(|v| best.contains(v)).collect(),
+            similarity: jaccard(&inner, &best),
+            route,
+        })
+        .collect();
+    Alternatives { routes, shortfall }
+}
+
+/// The `k` loopless routes from `start` to `goal` with the fewest jumps, shortest first. Ties
+/// are broken the same way on every run.
+pub fn k_shortest_paths(graph: &StarGraph, start: usize, goal: usize, k: usize) -> Vec<Vec<usize>> {
+    ShortestPaths::new(graph, start, goal).take(k).collect()
+}
+
+/// The systems of a route other than its endpoints, in route order.
+fn inner_order(route: &[usize]) -> impl Iterator<Item = usize> + '_ {
+    route
+        .iter()
+        .copied()
+        .skip(1)
+        .take(route.len().saturating_sub(2))
+}
+
+fn interior(route: &[usize]) -> HashSet<usize> {
+    inner_order(route).collect()
+}
+
+/// Shared systems over systems in either set; two direct hops count as identical.
+fn jaccard(a: &HashSet<usize>, b: &HashSet<usize>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Yen's algorithm as an iterator over unweighted routes. Neighbours are visited in index order
+/// so that every run lists the same routes.
+struct ShortestPaths {
+    neighbours: Vec<Vec<usize>>,
+    goal: usize,
+    found: Vec<Vec<usize>>,
+    /// Routes not returned yet, by jumps and then systems.
+    candidates: BTreeSet<(usize, Vec<usize>)>,
+}
+
+impl ShortestPaths {
+    fn new(graph: &StarGraph, start: usize, goal: usize) -> Self {
+        let neighbours = simple_neighbours(graph);
+        let mut candidates = BTreeSet::new();
+        let blocked = vec![false; neighbours.len()];
+        if let Some(route) = fewest_jumps(&neighbours, start, goal, &blocked, &HashSet::new()) {
+            candidates.insert((route.len() - 1, route));
+        }
+        ShortestPaths {
+            neighbours,
+            goal,
+            found: Vec::new(),
+            candidates,
+        }
+    }
+
+    /// Add the deviations of the last route found: for each system on it, the shortest route
+    /// that follows it up to there and then leaves it by an edge no earlier route with the same
+    /// beginning took, without revisiting the beginning.
+    fn branch(&mut self) {
+        let last = self.found.last().expect("branch follows a route");
+        let mut blocked = vec![false; self.neighbours.len()];
+        for i in 0..last.len() - 1 {
+            let root = &last[..=i];
+            let mut cut = HashSet::new();
+            for route in &self.found {
+                if route.len() > i + 1 && route[..=i] == *root {
+                    cut.insert((route[i], route[i + 1]));
+                }
+            }
+            if let Some(spur) = fewest_jumps(&self.neighbours, last[i], self.goal, &blocked, &cut) {
+                let mut route = root[..i].to_vec();
+                route.extend(spur);
+                self.candidates.insert((route.len() - 1, route));
+            }
+            blocked[last[i]] = true;
+        }
+    }
+}
+
+impl Iterator for ShortestPaths {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if !self.found.is_empty() {
+            self.branch();
+        }
+        let (_, route) = self.candidates.pop_first()?;
+        self.found.push(route.clone());
+        Some(route)
+    }
+}
+
+/// Breadth-first route from `start` to `goal` that avoids `blocked` systems and the `cut` edges
+/// leaving `start`; the lowest-indexed neighbour wins ties.
+fn fewest_jumps(
+    neighbours: &[Vec<usize>],
+    start: usize,
+    goal: usize,
+    blocked: &[bool],
+    cut: &HashSet<(usize, usize)>,
+) -> Option<Vec<usize>> {
+    let mut parent = vec![usize::MAX; neighbours.len()];
+    parent[start] = start;
+    let mut queue = VecDeque::from([start]);
+    while let Some(v) = queue.pop_front() {
+        if v == goal {
+            let mut route = vec![goal];
+            let mut at = goal;
+            while at != start {
+                at = parent[at];
+                route.push(at);
+            }
+            route.reverse();
+            return Some(route);
+        }
+        for &w in &neighbours[v] {
+            if parent[w] != usize::MAX || blocked[w] || (v == start && cut.contains(&(v, w))) {
+                continue;
+            }
+            parent[w] = v;
+            queue.push_back(w);
+        }
+    }
+    None
+}
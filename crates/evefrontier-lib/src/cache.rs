@@ -0,0 +1,377 @@
+use crate::error::{Error, ErrorKind, Result, ResultExt};
+use crate::install::{archive_stem, ArchiveKind};
+use crate::snapshot::snapshot_path;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub use crate::db::sha256_file;
+
+const METADATA_SUFFIX: &str = ".meta.json";
+const LOCK_FILE: &str = ".lock";
+/// Subdirectory for assets installed with [`install_from`], kept apart from release assets so a
+/// local build never replaces a release asset of the same name.
+///
+/// [`install_from`]: crate::install_from
+pub(crate) const LOCAL_DIR: &str = "local";
+
+/// Provenance recorded next to each cached asset as `<asset>.meta.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssetMetadata {
+    pub release_tag: Option<String>,
+    pub source: Option<String>,
+    pub sha256: Option<String>,
+    pub size: Option<u64>,
+    /// Seconds since the Unix epoch.
+    pub downloaded_at: Option<u64>,
+    /// Seconds since the Unix epoch.
+    pub last_used: Option<u64>,
+    /// For an installed DB: the cached asset it came from, named as in [`CacheEntry::name`].
+    pub asset: Option<String>,
+    /// For an installed DB extracted from an archive: the file name of the entry.
+    pub entry: Option<String>,
+}
+
+/// A cached release asset together with the databases extracted from it.
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    /// The file name, prefixed with `local/` for assets installed from a local file or URL.
+    pub name: String,
+    pub path: PathBuf,
+    /// Databases extracted from an archive asset, stored in a directory named after the asset.
+    pub extracted: Vec<PathBuf>,
+    /// Size of the asset plus everything extracted from it, in bytes.
+    pub size: u64,
+    pub sha256: String,
+    pub release_tag: Option<String>,
+    pub source: Option<String>,
+    pub last_used: SystemTime,
+}
+
+impl CacheEntry {
+    /// Whether `path` is this asset or one of the databases extracted from it.
+    pub fn contains(&self, path: &Path) -> bool {
+        std::iter::once(&self.path)
+            .chain(self.extracted.iter())
+            .any(|p| same_file(p, path))
+    }
+}
+
+/// Which cached assets [`prune_cache`] removes. An asset is removed when it falls outside the
+/// `keep` most recently used assets, or when it was last used more than `older_than` ago.
+#[derive(Debug, Clone, Default)]
+pub struct PrunePolicy {
+    pub older_than: Option<Duration>,
+    pub keep: Option<usize>,
+}
+
+/// The directory release assets are cached in: `evefrontier_datasets/` under the OS cache dir.
+pub fn default_cache_root() -> Result<PathBuf> {
+    Ok(dirs::cache_dir()
+        .ok_or_else(|| Error::io("Could not determine user cache directory"))?
+        .join("evefrontier_datasets"))
+}
+
+/// An exclusive lock on a cache directory, released when dropped.
+///
+/// Downloads, installs and pruning hold it while they touch the cache, so concurrent processes
+/// (and the blocking and async downloaders) never see a half-written asset or remove one that
+/// is being extracted.
+pub(crate) struct CacheLock {
+    _file: fs::File,
+}
+
+/// Block until the cache under `root` can be locked exclusively, creating `root` if needed.
+pub(crate) fn lock_cache(root: &Path) -> Result<CacheLock> {
+    fs::create_dir_all(root).context(ErrorKind::Io, || {
+        format!("failed to create cache directory {}", root.display())
+    })?;
+    let path = root.join(LOCK_FILE);
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .context(ErrorKind::Io, || {
+            format!("failed to open cache lock {}", path.display())
+        })?;
+    file.lock_exclusive().context(ErrorKind::Io, || {
+        format!("failed to lock cache {}", root.display())
+    })?;
+    Ok(CacheLock { _file: file })
+}
+
+fn file_len(path: &Path) -> Result<u64> {
+    fs::metadata(path)
+        .map(|m| m.len())
+        .context(ErrorKind::Io, || {
+            format!("failed to stat {}", path.display())
+        })
+}
+
+fn metadata_path(asset: &Path) -> PathBuf {
+    let mut name = asset.as_os_str().to_os_string();
+    name.push(METADATA_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Read the metadata sidecar of `asset`, if there is one.
+pub fn read_metadata(asset: &Path) -> Result<Option<AssetMetadata>> {
+    let path = metadata_path(asset);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path).context(ErrorKind::Io, || {
+        format!("failed to read cache metadata {}", path.display())
+    })?;
+    let meta = serde_json::from_str(&text).context(ErrorKind::Io, || {
+        format!("failed to parse cache metadata {}", path.display())
+    })?;
+    Ok(Some(meta))
+}
+
+/// Write the metadata sidecar of `asset`.
+pub fn write_metadata(asset: &Path, meta: &AssetMetadata) -> Result<()> {
+    let path = metadata_path(asset);
+    let text = serde_json::to_string_pretty(meta).context(ErrorKind::Io, || {
+        format!("failed to serialize cache metadata {}", path.display())
+    })?;
+    fs::write(&path, text).context(ErrorKind::Io, || {
+        format!("failed to write cache metadata {}", path.display())
+    })
+}
+
+/// Record where a freshly downloaded asset came from, along with its digest.
+pub fn record_download(asset: &Path, release_tag: Option<&str>, source: &str) -> Result<()> {
+    let now = now_secs();
+    let meta = AssetMetadata {
+        release_tag: release_tag.map(str::to_string),
+        source: Some(source.to_string()),
+        sha256: Some(sha256_file(asset)?),
+        size: Some(file_len(asset)?),
+        downloaded_at: Some(now),
+        last_used: Some(now),
+        ..AssetMetadata::default()
+    };
+    write_metadata(asset, &meta)
+}
+
+/// Mark a cached asset as used now, so [`prune_cache`] keeps it longer.
+pub fn touch(asset: &Path) -> Result<()> {
+    let mut meta = read_metadata(asset)?.unwrap_or_default();
+    meta.last_used = Some(now_secs());
+    write_metadata(asset, &meta)
+}
+
+/// List the cached assets under `root`, release and local ones together, most recently used
+/// first.
+///
+/// Digests missing from the metadata sidecar are computed and saved.
+pub fn list_cache(root: &Path) -> Result<Vec<CacheEntry>> {
+    let mut entries = Vec::new();
+    list_assets(root, "", &mut entries)?;
+    list_assets(&root.join(LOCAL_DIR), "local/", &mut entries)?;
+    entries.sort_by(|a, b| b.last_used.cmp(&a.last_used).then(a.name.cmp(&b.name)));
+    Ok(entries)
+}
+
+/// Add the assets directly inside `dir` to `entries`, naming each with `prefix`.
+fn list_assets(dir: &Path, prefix: &str, entries: &mut Vec<CacheEntry>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    let read_err = || format!("failed to read cache directory {}", dir.display());
+    for item in fs::read_dir(dir).context(ErrorKind::Io, read_err)? {
+        let path = item.context(ErrorKind::Io, read_err)?.path();
+        let is_asset = path.is_file()
+            && path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(ArchiveKind::from_name)
+                .is_some();
+        if is_asset {
+            let mut entry = load_entry(&path)?;
+            entry.name.insert_str(0, prefix);
+            entries.push(entry);
+        }
+    }
+    Ok(())
+}
+
+/// Look up a single cached asset by file name.
+pub fn cache_entry(root: &Path, name: &str) -> Result<CacheEntry> {
+    list_cache(root)?
+        .into_iter()
+        .find(|e| e.name == name)
+        .ok_or_else(|| {
+            Error::invalid_input(format!(
+                "No cached asset named '{}' in {}",
+                name,
+                root.display()
+            ))
+        })
+}
+
+fn load_entry(path: &Path) -> Result<CacheEntry> {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_string();
+    let file_meta = fs::metadata(path).context(ErrorKind::Io, || {
+        format!("failed to stat {}", path.display())
+    })?;
+    let mut meta = read_metadata(path)?.unwrap_or_default();
+
+    let sha256 = match &meta.sha256 {
+        Some(digest) if meta.size == Some(file_meta.len()) => digest.clone(),
+        _ => {
+            let digest = sha256_file(path)?;
+            meta.sha256 = Some(digest.clone());
+            meta.size = Some(file_meta.len());
+            // The cache may be read-only; a missing sidecar only costs a recomputation.
+            let _ = write_metadata(path, &meta);
+            digest
+        }
+    };
+
+    let extracted = extracted_files(path)?;
+    let mut size = file_meta.len();
+    for file in &extracted {
+        size += file_len(file)?;
+    }
+
+    let last_used = meta
+        .last_used
+        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+        .or_else(|| file_meta.modified().ok())
+        .unwrap_or(UNIX_EPOCH);
+
+    Ok(CacheEntry {
+        name,
+        path: path.to_path_buf(),
+        extracted,
+        size,
+        sha256,
+        release_tag: meta.release_tag,
+        source: meta.source,
+        last_used,
+    })
+}
+
+/// Archives are extracted into a directory named after the asset, without its extension.
+pub(crate) fn extraction_dir(asset: &Path) -> Option<PathBuf> {
+    let name = asset.file_name()?.to_str()?;
+    Some(asset.with_file_name(archive_stem(name)?))
+}
+
+fn extracted_files(asset: &Path) -> Result<Vec<PathBuf>> {
+    let Some(dir) = extraction_dir(asset).filter(|d| d.is_dir()) else {
+        return Ok(Vec::new());
+    };
+    let mut files = Vec::new();
+    let read_err = || format!("failed to read extraction directory {}", dir.display());
+    for item in fs::read_dir(&dir).context(ErrorKind::Io, read_err)? {
+        let path = item.context(ErrorKind::Io, read_err)?.path();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Remove a cached asset with its metadata, the snapshot of a bare `.db` asset and anything
+/// extracted from it, if present. Callers hold the cache lock.
+pub(crate) fn remove_asset(asset: &Path) -> Result<()> {
+    let files = [
+        asset.to_path_buf(),
+        metadata_path(asset),
+        snapshot_path(asset),
+    ];
+    for file in files {
+        if file.exists() {
+            fs::remove_file(&file).context(ErrorKind::Io, || {
+                format!("failed to remove {}", file.display())
+            })?;
+        }
+    }
+    if let Some(dir) = extraction_dir(asset).filter(|d| d.is_dir()) {
+        fs::remove_dir_all(&dir).context(ErrorKind::Io, || {
+            format!("failed to remove {}", dir.display())
+        })?;
+    }
+    Ok(())
+}
+
+/// The cached asset under `root` that the DB at `db` was installed from, according to its
+/// metadata sidecar. `None` when the DB has no recorded asset or the asset is gone.
+pub fn installed_asset(root: &Path, db: &Path) -> Result<Option<PathBuf>> {
+    let Some(name) = read_metadata(db)?.and_then(|meta| meta.asset) else {
+        return Ok(None);
+    };
+    let asset = root.join(name);
+    Ok(asset.is_file().then_some(asset))
+}
+
+/// Remove the cached assets selected by `policy` and return them.
+///
+/// Assets that contain any of the `protected` paths (for example the [`installed_asset`] of
+/// the DB currently in use) are never removed.
+pub fn prune_cache(
+    root: &Path,
+    policy: &PrunePolicy,
+    protected: &[&Path],
+) -> Result<Vec<CacheEntry>> {
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+    let _lock = lock_cache(root)?;
+    let now = SystemTime::now();
+    let mut removed = Vec::new();
+    for (rank, entry) in list_cache(root)?.into_iter().enumerate() {
+        let beyond_keep = policy.keep.is_some_and(|keep| rank >= keep);
+        let too_old = policy.older_than.is_some_and(|max_age| {
+            now.duration_since(entry.last_used).unwrap_or_default() > max_age
+        });
+        if !(beyond_keep || too_old) || protected.iter().any(|p| entry.contains(p)) {
+            continue;
+        }
+        remove_asset(&entry.path)?;
+        removed.push(entry);
+    }
+    Ok(removed)
+}
+
+/// Remove every cached asset except those containing one of the `protected` paths.
+pub fn clear_cache(root: &Path, protected: &[&Path]) -> Result<Vec<CacheEntry>> {
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+    let _lock = lock_cache(root)?;
+    let mut removed = Vec::new();
+    for entry in list_cache(root)? {
+        if protected.iter().any(|p| entry.contains(p)) {
+            continue;
+        }
+        remove_asset(&entry.path)?;
+        removed.push(entry);
+    }
+    Ok(removed)
+}
@@ -0,0 +1,243 @@
+//! Degree, betweenness and closeness centrality of the gate network, for finding hub systems.
+//!
+//! Betweenness and closeness come from one breadth-first search per source system (Brandes'
+//! algorithm), counting gate jumps. On the full map that is tens of thousands of searches, so
+//! [`CentralityConfig::samples`] can limit them to a seeded random subset of sources and scale
+//! the results up, trading accuracy for time. With the `parallel` feature the searches run on
+//! the rayon thread pool; the results are identical either way.
+
+use crate::error::{Error, Result};
+use crate::graph::{simple_neighbours, StarGraph};
+use serde::Serialize;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CentralityConfig {
+    /// Search from this many randomly chosen systems instead of all of them. `None`, or a
+    /// number at least the system count, gives exact values; `Some(0)` is rejected.
+    pub samples: Option<usize>,
+    /// Seed for choosing the sampled systems.
+    pub seed: u64,
+}
+
+/// The centrality of one system.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Centrality {
+    pub system: usize,
+    /// Number of systems one gate away.
+    pub degree: usize,
+    /// Share of the shortest routes between other pairs of systems that pass through this one,
+    /// from 0 to 1.
+    pub betweenness: f64,
+    /// How few jumps it takes to reach the rest of the map, from 0 to 1. This is the inverse of
+    /// the mean distance to the systems of the same component, scaled by the share of the map
+    /// that component covers, so a small isolated cluster does not outrank the main network.
+    pub closeness: f64,
+}
+
+/// The measure to rank systems by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CentralityMetric {
+    Degree,
+    Betweenness,
+    Closeness,
+}
+
+impl CentralityMetric {
+    pub fn value(self, centrality: &Centrality) -> f64 {
+        match self {
+            CentralityMetric::Degree => centrality.degree as f64,
+            CentralityMetric::Betweenness => centrality.betweenness,
+            CentralityMetric::Closeness => centrality.closeness,
+        }
+    }
+}
+
+/// Searches are split into this many batches, always summed in the same order, so results do
+/// not depend on thread scheduling or on whether the `parallel` feature is enabled.
+const BATCHES: usize = 64;
+
+/// The centrality of every system, in system index order. Fails with an invalid-input error
+/// when `config.samples` is `Some(0)`, which would leave every score at zero.
+pub fn compute_centrality(graph: &StarGraph, config: &CentralityConfig) -> Result<Vec<Centrality>> {
+    if config.samples == Some(0) {
+        return Err(Error::invalid_input(
+            "centrality needs at least one sampled system",
+        ));
+    }
+    let n = graph.node_count();
+    let neighbours = simple_neighbours(graph);
+    let sources = sample_sources(n, config);
+    let batch_len = sources.len().div_ceil(BATCHES).max(1);
+    let batches: Vec<&[usize]> = sources.chunks(batch_len).collect();
+
+    #[cfg(feature = "parallel")]
+    let partials: Vec<Totals> = {
+        use rayon::prelude::*;
+        batches
+            .par_iter()
+            .map(|batch| search_from(&neighbours, batch))
+            .collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let partials: Vec<Totals> = batches
+        .iter()
+        .map(|batch| search_from(&neighbours, batch))
+        .collect();
+
+    let mut totals = Totals::new(n);
+    for partial in partials {
+        totals.add(&partial);
+    }
+
+    // Each unordered pair is counted once from either end, hence the halving; sampling sees
+    // only `sources.len()` of the `n` searches.
+    let scale = if sources.is_empty() {
+        0.0
+    } else {
+        n as f64 / sources.len() as f64
+    };
+    let pairs = n.saturating_sub(1) as f64 * n.saturating_sub(2) as f64 / 2.0;
+    Ok((0..n)
+        .map(|v| {
+            let betweenness = if pairs > 0.0 {
+                (totals.dependency[v] * scale / 2.0 / pairs).min(1.0)
+            } else {
+                0.0
+            };
+            let closeness = if totals.distance[v] > 0 {
+                let reached = totals.reached[v] as f64;
+                let coverage = (reached * scale / (n - 1) as f64).min(1.0);
+                coverage * reached / totals.distance[v] as f64
+            } else {
+                0.0
+            };
+            Centrality {
+                system: v,
+                degree: neighbours[v].len(),
+                betweenness,
+                closeness,
+            }
+        })
+        .collect())
+}
+
+/// `scores` sorted by `metric`, highest first; ties keep index order.
+pub fn rank_by(scores: &[Centrality], metric: CentralityMetric) -> Vec<Centrality> {
+    let mut ranked = scores.to_vec();
+    ranked.sort_by(|a, b| {
+        metric
+            .value(b)
+            .total_cmp(&metric.value(a))
+            .then(a.system.cmp(&b.system))
+    });
+    ranked
+}
+
+/// Per-system sums over a batch of searches.
+struct Totals {
+    /// Brandes dependency: shortest routes from the sources that pass through the system.
+    dependency: Vec<f64>,
+    /// Jumps from the sources that reach the system, and how many of them do.
+    distance: Vec<u64>,
+    reached: Vec<u64>,
+}
+
+impl Totals {
+    fn new(n: usize) -> Self {
+        Totals {
+            dependency: vec![0.0; n],
+            distance: vec![0; n],
+            reached: vec![0; n],
+        }
+    }
+
+    fn add(&mut self, other: &Totals) {
+        for (a, b) in self.dependency.iter_mut().zip(&other.dependency) {
+            *a += b;
+        }
+        for (a, b) in self.distance.iter_mut().zip(&other.distance) {
+            *a += b;
+        }
+        for (a, b) in self.reached.iter_mut().zip(&other.reached) {
+            *a += b;
+        }
+    }
+}
+
+fn search_from(neighbours: &[Vec<usize>], sources: &[usize]) -> Totals {
+    const UNSEEN: usize = usize::MAX;
+    let n = neighbours.len();
+    let mut totals = Totals::new(n);
+    let mut dist = vec![UNSEEN; n];
+    let mut paths = vec![0f64; n];
+    let mut delta = vec![0f64; n];
+    let mut order = Vec::with_capacity(n);
+    let mut queue = VecDeque::new();
+
+    for &s in sources {
+        dist[s] = 0;
+        paths[s] = 1.0;
+        queue.push_back(s);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            for &w in &neighbours[v] {
+                if dist[w] == UNSEEN {
+                    dist[w] = dist[v] + 1;
+                    queue.push_back(w);
+                }
+                if dist[w] == dist[v] + 1 {
+                    paths[w] += paths[v];
+                }
+            }
+        }
+
+        // Farthest first, so every system's dependency is complete before it is passed on.
+        for &w in order.iter().rev() {
+            for &v in &neighbours[w] {
+                if dist[v] != UNSEEN && dist[v] + 1 == dist[w] {
+                    delta[v] += paths[v] / paths[w] * (1.0 + delta[w]);
+                }
+            }
+            if w != s {
+                totals.dependency[w] += delta[w];
+                totals.distance[w] += dist[w] as u64;
+                totals.reached[w] += 1;
+            }
+        }
+
+        for &v in &order {
+            dist[v] = UNSEEN;
+            paths[v] = 0.0;
+            delta[v] = 0.0;
+        }
+        order.clear();
+    }
+    totals
+}
+
+/// All system indices, or `config.samples` of them chosen by a seeded partial Fisher-Yates
+/// shuffle, in ascending order.
+fn sample_sources(n: usize, config: &CentralityConfig) -> Vec<usize> {
+    let mut sources: Vec<usize> = (0..n).collect();
+    let Some(samples) = config.samples.filter(|&k| k < n) else {
+        return sources;
+    };
+    let mut state = config.seed;
+    for i in 0..samples {
+        let j = i + (splitmix64(&mut state) % (n - i) as u64) as usize;
+        sources.swap(i, j);
+    }
+    sources.truncate(samples);
+    sources.sort_unstable();
+    sources
+}
+
+/// A small, stable generator, so a seed picks the same systems in every release.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
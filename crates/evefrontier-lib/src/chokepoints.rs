@@ -0,0 +1,132 @@
+//! Systems and gates whose loss would split the gate network: articulation points and bridges,
+//! each with the number of systems it cuts off.
+//!
+//! Removing a chokepoint splits its component into pieces. The systems it cuts off are those
+//! outside the largest remaining piece, so a gate to a dead-end pocket of three systems cuts off
+//! three, however large the rest of the map is.
+
+use crate::graph::StarGraph;
+use petgraph::graph::NodeIndex;
+use serde::Serialize;
+
+/// A system whose removal disconnects its component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArticulationPoint {
+    pub system: usize,
+    /// Systems left outside the largest piece once `system` is gone.
+    pub cut_off: usize,
+    /// Number of pieces the component falls into.
+    pub pieces: usize,
+}
+
+/// A gate whose removal disconnects its component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Bridge {
+    pub a: usize,
+    pub b: usize,
+    /// Systems on the smaller side of the gate.
+    pub cut_off: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Chokepoints {
+    /// Ranked by the number of systems cut off, then by pieces, then by index.
+    pub articulation_points: Vec<ArticulationPoint>,
+    /// Ranked by the number of systems cut off, then by index.
+    pub bridges: Vec<Bridge>,
+}
+
+/// Find every articulation point and bridge with Tarjan's low-link algorithm, using an explicit
+/// stack so that long chains of systems cannot overflow the call stack.
+pub fn find_chokepoints(graph: &StarGraph) -> Chokepoints {
+    const UNSEEN: usize = usize::MAX;
+    let n = graph.node_count();
+    let neighbours = |v: usize| -> Vec<usize> {
+        graph
+            .neighbors(NodeIndex::new(v))
+            .map(|w| w.index())
+            .collect()
+    };
+    let mut disc = vec![UNSEEN; n];
+    let mut low = vec![0; n];
+    let mut parent = vec![UNSEEN; n];
+    let mut size = vec![1usize; n];
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut timer = 0;
+    let mut result = Chokepoints::default();
+
+    for root in 0..n {
+        if disc[root] != UNSEEN {
+            continue;
+        }
+        let mut component = vec![root];
+        disc[root] = timer;
+        low[root] = timer;
+        timer += 1;
+        let mut stack = vec![(root, neighbours(root), 0)];
+        while let Some((v, next, cursor)) = stack.last_mut() {
+            let v = *v;
+            if let Some(&w) = next.get(*cursor) {
+                *cursor += 1;
+                if disc[w] == UNSEEN {
+                    parent[w] = v;
+                    disc[w] = timer;
+                    low[w] = timer;
+                    timer += 1;
+                    component.push(w);
+                    stack.push((w, neighbours(w), 0));
+                } else if w != parent[v] {
+                    low[v] = low[v].min(disc[w]);
+                }
+            } else {
+                stack.pop();
+                let p = parent[v];
+                if p != UNSEEN {
+                    low[p] = low[p].min(low[v]);
+                    size[p] += size[v];
+                    children[p].push(v);
+                }
+            }
+        }
+
+        let total = size[root];
+        for &v in &component {
+            let mut pieces: Vec<usize> = children[v]
+                .iter()
+                .filter(|&&c| v == root || low[c] >= disc[v])
+                .map(|&c| size[c])
+                .collect();
+            if v != root && !pieces.is_empty() {
+                pieces.push(total - 1 - pieces.iter().sum::<usize>());
+            }
+            if pieces.len() >= 2 {
+                let largest = pieces.iter().copied().max().unwrap_or(0);
+                result.articulation_points.push(ArticulationPoint {
+                    system: v,
+                    cut_off: pieces.iter().sum::<usize>() - largest,
+                    pieces: pieces.len(),
+                });
+            }
+            for &c in &children[v] {
+                if low[c] > disc[v] {
+                    result.bridges.push(Bridge {
+                        a: v.min(c),
+                        b: v.max(c),
+                        cut_off: size[c].min(total - size[c]),
+                    });
+                }
+            }
+        }
+    }
+
+    result.articulation_points.sort_by(|x, y| {
+        y.cut_off
+            .cmp(&x.cut_off)
+            .then(y.pieces.cmp(&x.pieces))
+            .then(x.system.cmp(&y.system))
+    });
+    result
+        .bridges
+        .sort_by(|x, y| y.cut_off.cmp(&x.cut_off).then((x.a, x.b).cmp(&(y.a, y.b))));
+    result
+}
@@ -0,0 +1,149 @@
+//! Connected components of the gate network. Systems in different components cannot reach each
+//! other by gate, so routes and tours only ever cover the component they start in.
+
+use crate::db::System;
+use crate::graph::StarGraph;
+use crate::path::optimal_route_with_progress;
+use crate::progress::ProgressReporter;
+use crate::starmap::Starmap;
+use petgraph::graph::NodeIndex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// Indices of the systems reachable from `start`, including `start`, in ascending order.
+pub fn component_of(graph: &StarGraph, start: usize) -> Vec<usize> {
+    let mut seen = vec![false; graph.node_count()];
+    let mut members = flood(graph, start, &mut seen);
+    members.sort_unstable();
+    members
+}
+
+fn flood(graph: &StarGraph, start: usize, seen: &mut [bool]) -> Vec<usize> {
+    let mut members = vec![start];
+    let mut queue = VecDeque::from([start]);
+    seen[start] = true;
+    while let Some(idx) = queue.pop_front() {
+        for next in graph.neighbors(NodeIndex::new(idx)) {
+            if !seen[next.index()] {
+                seen[next.index()] = true;
+                members.push(next.index());
+                queue.push_back(next.index());
+            }
+        }
+    }
+    members
+}
+
+/// Every connected component as ascending system indices, largest first; components of equal
+/// size are ordered by their lowest index.
+pub fn connected_components(graph: &StarGraph) -> Vec<Vec<usize>> {
+    let mut seen = vec![false; graph.node_count()];
+    let mut components = Vec::new();
+    for start in 0..graph.node_count() {
+        if !seen[start] {
+            let mut members = flood(graph, start, &mut seen);
+            members.sort_unstable();
+            components.push(members);
+        }
+    }
+    components.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+    components
+}
+
+/// Indices of the systems that cannot be reached from `start`, in ascending order.
+pub fn unreachable_from(graph: &StarGraph, start: usize) -> Vec<usize> {
+    let mut seen = vec![false; graph.node_count()];
+    flood(graph, start, &mut seen);
+    (0..seen.len()).filter(|&i| !seen[i]).collect()
+}
+
+/// One connected component, for reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ComponentSummary {
+    pub size: usize,
+    /// Names (or ids) of the regions with systems in the component, sorted.
+    pub regions: Vec<String>,
+    /// System names in index order.
+    pub systems: Vec<String>,
+}
+
+/// [`connected_components`] of the star map, with region and system names.
+pub fn summarize_components(starmap: &Starmap) -> Vec<ComponentSummary> {
+    connected_components(&starmap.graph)
+        .into_iter()
+        .map(|members| {
+            let systems: Vec<&System> = members.iter().map(|&i| &starmap.systems[i]).collect();
+            let regions: BTreeSet<String> =
+                systems.iter().filter_map(|s| region_label(s)).collect();
+            ComponentSummary {
+                size: members.len(),
+                regions: regions.into_iter().collect(),
+                systems: systems.iter().map(|s| s.name.clone()).collect(),
+            }
+        })
+        .collect()
+}
+
+/// The name of the region `system` is in, or its id when the dataset has no region names.
+pub fn region_label(system: &System) -> Option<String> {
+    system
+        .region
+        .clone()
+        .or(system.region_id.map(|id| id.to_string()))
+}
+
+/// A region with systems outside the component a route starts in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnreachableRegion {
+    /// The region name, its id when the name is unknown, or `None` for systems without one.
+    pub region: Option<String>,
+    pub unreachable: usize,
+    pub total: usize,
+}
+
+impl UnreachableRegion {
+    /// Whether no system of the region can be reached.
+    pub fn is_entirely_unreachable(&self) -> bool {
+        self.unreachable == self.total
+    }
+}
+
+/// Group `unreachable` system indices by region, ordered by region label.
+pub fn unreachable_regions(systems: &[System], unreachable: &[usize]) -> Vec<UnreachableRegion> {
+    let mut regions: BTreeMap<Option<String>, (usize, usize)> = BTreeMap::new();
+    for &idx in unreachable {
+        regions.entry(region_label(&systems[idx])).or_default().0 += 1;
+    }
+    for system in systems {
+        if let Some(counts) = regions.get_mut(&region_label(system)) {
+            counts.1 += 1;
+        }
+    }
+    regions
+        .into_iter()
+        .map(|(region, (unreachable, total))| UnreachableRegion {
+            region,
+            unreachable,
+            total,
+        })
+        .collect()
+}
+
+/// A tour of every component: the component of `start` from `start`, then each other component,
+/// largest first, from its lowest-indexed system. Each tour is an [`optimal_route`] within its
+/// component.
+///
+/// [`optimal_route`]: crate::optimal_route
+pub fn tour_components(
+    graph: &StarGraph,
+    start: usize,
+    progress: &dyn ProgressReporter,
+) -> Vec<Vec<usize>> {
+    let mut tours = vec![optimal_route_with_progress(graph, start, progress)];
+    for component in connected_components(graph) {
+        if !component.contains(&start) {
+            tours.push(optimal_route_with_progress(graph, component[0], progress));
+        }
+    }
+    tours
+}
@@ -0,0 +1,196 @@
+//! The TOML configuration file. Its `[schema]` section maps the logical fields the loader reads
+//! to the table and column names of datasets that differ from the official extract:
+//!
+//! ```toml
+//! [schema.systems]
+//! table = "mapSolarSystems"
+//! id = "solarSystemID"
+//! name = "solarSystemName"
+//! region = "regionID"
+//! constellation = "constellationID"
+//! x = "x"
+//! y = "y"
+//! z = "z"
+//!
+//! [schema.jumps]
+//! table = "Stargates"
+//! from = "fromSystemID"
+//! to = "toSystemID"
+//!
+//! [schema.regions]
+//! table = "mapRegions"
+//! id = "regionID"
+//! name = "regionName"
+//! ```
+//!
+//! Every key is optional. Mapped names are used as given and must exist in the dataset; anything
+//! left out is found by the loader's built-in detection.
+//!
+//! `[[ships]]` tables define [`ShipProfile`]s for routing with a jump drive.
+
+use crate::error::{Error, ErrorKind, Result, ResultExt};
+use crate::ship::ShipProfile;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub schema: SchemaMapping,
+    #[serde(default)]
+    pub ships: Vec<ShipProfile>,
+}
+
+impl Config {
+    /// Read and validate the configuration file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).context(ErrorKind::Io, || {
+            format!("failed to read config {}", path.display())
+        })?;
+        Config::from_toml(&text)
+            .map_err(|err| Error::invalid_input(format!("{}: {}", path.display(), err)))
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let config: Config = toml::from_str(text)
+            .map_err(|err| Error::invalid_input(format!("invalid config: {}", err)))?;
+        config.schema.validate()?;
+        for (i, ship) in config.ships.iter().enumerate() {
+            ship.validate()?;
+            if config.ships[..i].iter().any(|s| s.name == ship.name) {
+                return Err(Error::invalid_input(format!(
+                    "ship {} is defined more than once",
+                    ship.name
+                )));
+            }
+        }
+        Ok(config)
+    }
+
+    /// The ship profile called `name`.
+    pub fn ship(&self, name: &str) -> Result<&ShipProfile> {
+        self.ships.iter().find(|s| s.name == name).ok_or_else(|| {
+            let known: Vec<&str> = self.ships.iter().map(|s| s.name.as_str()).collect();
+            Error::invalid_input(if known.is_empty() {
+                format!(
+                    "unknown ship {}; the config file defines no [[ships]]",
+                    name
+                )
+            } else {
+                format!("unknown ship {} (configured: {})", name, known.join(", "))
+            })
+        })
+    }
+}
+
+/// Table and column names for the loader to use instead of detecting them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaMapping {
+    #[serde(default)]
+    pub systems: SystemsMapping,
+    #[serde(default)]
+    pub jumps: JumpsMapping,
+    #[serde(default)]
+    pub regions: LookupMapping,
+    #[serde(default)]
+    pub constellations: LookupMapping,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SystemsMapping {
+    pub table: Option<String>,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub region: Option<String>,
+    pub constellation: Option<String>,
+    pub x: Option<String>,
+    pub y: Option<String>,
+    pub z: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JumpsMapping {
+    pub table: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// A name lookup table such as `Regions`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LookupMapping {
+    pub table: Option<String>,
+    pub id: Option<String>,
+    pub name: Option<String>,
+}
+
+impl SchemaMapping {
+    /// Check the mapping on its own, before it meets a dataset: no empty names, and coordinates
+    /// mapped all together or not at all.
+    pub fn validate(&self) -> Result<()> {
+        for (key, value) in self.entries() {
+            if value.is_some_and(|v| v.trim().is_empty()) {
+                return Err(Error::invalid_input(format!(
+                    "schema mapping {} is empty",
+                    key
+                )));
+            }
+        }
+        let s = &self.systems;
+        let coordinates = [&s.x, &s.y, &s.z];
+        if coordinates.iter().any(|c| c.is_some()) && !coordinates.iter().all(|c| c.is_some()) {
+            return Err(Error::invalid_input(
+                "schema mapping must set all of systems.x, systems.y and systems.z, or none",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether nothing is mapped, so the loader relies on detection alone.
+    pub fn is_empty(&self) -> bool {
+        self.entries().iter().all(|(_, value)| value.is_none())
+    }
+
+    /// Every mapping key with its value, named as in the config file.
+    fn entries(&self) -> [(&'static str, Option<&String>); 17] {
+        let (s, j, r, c) = (
+            &self.systems,
+            &self.jumps,
+            &self.regions,
+            &self.constellations,
+        );
+        [
+            ("systems.table", s.table.as_ref()),
+            ("systems.id", s.id.as_ref()),
+            ("systems.name", s.name.as_ref()),
+            ("systems.region", s.region.as_ref()),
+            ("systems.constellation", s.constellation.as_ref()),
+            ("systems.x", s.x.as_ref()),
+            ("systems.y", s.y.as_ref()),
+            ("systems.z", s.z.as_ref()),
+            ("jumps.table", j.table.as_ref()),
+            ("jumps.from", j.from.as_ref()),
+            ("jumps.to", j.to.as_ref()),
+            ("regions.table", r.table.as_ref()),
+            ("regions.id", r.id.as_ref()),
+            ("regions.name", r.name.as_ref()),
+            ("constellations.table", c.table.as_ref()),
+            ("constellations.id", c.id.as_ref()),
+            ("constellations.name", c.name.as_ref()),
+        ]
+    }
+
+    /// A stable string identifying the mapping, stored in snapshots so a snapshot built with
+    /// one mapping is not reused with another.
+    pub(crate) fn fingerprint(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        serde_json::to_string(self).expect("a schema mapping always serializes")
+    }
+}
@@ -1,44 +1,441 @@
-use anyhow::{Context, Result};
-use rusqlite::Connection;
+use crate::config::{LookupMapping, SchemaMapping};
+use crate::error::{Error, ErrorKind, Result, ResultExt};
+use rusqlite::{Connection, ErrorCode, OpenFlags};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
 
-#[derive(Debug, Clone)]
+/// How long to wait for another process to release a lock on the database.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct System {
     pub id: i64,
     pub name: String,
+    /// Region and constellation membership, when the dataset records it.
+    pub region_id: Option<i64>,
+    pub region: Option<String>,
+    pub constellation_id: Option<i64>,
+    pub constellation: Option<String>,
+    /// Position in space in metres, when the dataset has coordinate columns.
+    pub position: Option<Position>,
 }
 
-#[derive(Debug, Clone)]
+/// A point in space, in metres.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// One light year in metres, the unit positions are stored in.
+pub const LIGHT_YEAR: f64 = 9.4607e15;
+
+impl Position {
+    /// Straight-line distance to `other`, in metres.
+    pub fn distance(self, other: Position) -> f64 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2) + (self.z - other.z).powi(2))
+            .sqrt()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Jump {
     pub from_id: i64,
     pub to_id: i64,
 }
 
-pub fn load_starmap(path: &std::path::Path) -> Result<(Vec<System>, Vec<Jump>)> {
-    let conn = Connection::open(path)
-        .with_context(|| format!("failed to open SQLite database {}", path.display()))?;
+/// Column names of `table`; empty when the table does not exist.
+pub(crate) fn table_columns(conn: &Connection, db: &str, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", quote_identifier(table)))
+        .context(ErrorKind::Database, || {
+            format!("{} is not a readable SQLite database", db)
+        })?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<_>>()
+        .context(ErrorKind::Database, || {
+            format!("{} is not a readable SQLite database", db)
+        })?;
+    Ok(columns)
+}
+
+/// The first of `candidates` that is one of `columns`, compared case-insensitively like SQLite.
+pub(crate) fn find_column<'a>(columns: &[String], candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .copied()
+        .find(|c| columns.iter().any(|col| col.eq_ignore_ascii_case(c)))
+}
+
+/// `name` quoted for use as an SQL identifier.
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Names of the tables and views in the database.
+fn table_names(conn: &Connection, db: &str) -> Result<Vec<String>> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type IN ('table', 'view') ORDER BY name")
+        .context(ErrorKind::Database, || {
+            format!("{} is not a readable SQLite database", db)
+        })?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<_>>()
+        .context(ErrorKind::Database, || {
+            format!("{} is not a readable SQLite database", db)
+        })?;
+    Ok(names)
+}
+
+/// A name lookup table such as `Regions`.
+#[derive(Debug, Clone)]
+pub(crate) struct Lookup {
+    pub(crate) table: String,
+    pub(crate) id: String,
+    pub(crate) name: String,
+}
+
+/// The tables and columns the loader reads: those a [`SchemaMapping`] names, and whatever it
+/// leaves out as found by the built-in detection. Optional data the dataset lacks is `None`;
+/// required names fall back to the official schema's so a dataset without them fails when read.
+#[derive(Debug, Clone)]
+pub(crate) struct ResolvedSchema {
+    pub(crate) systems: String,
+    pub(crate) system_id: String,
+    pub(crate) system_name: String,
+    pub(crate) region: Option<String>,
+    pub(crate) constellation: Option<String>,
+    pub(crate) coordinates: Option<[String; 3]>,
+    pub(crate) jumps: String,
+    pub(crate) jump_from: String,
+    pub(crate) jump_to: String,
+    pub(crate) region_names: Option<Lookup>,
+    pub(crate) constellation_names: Option<Lookup>,
+}
+
+/// Looks up mapped and detected names in one database, for [`resolve_schema`].
+struct Resolver<'a> {
+    conn: &'a Connection,
+    db: &'a str,
+    tables: Vec<String>,
+}
+
+impl Resolver<'_> {
+    /// The mapped table, which must exist, or `default`.
+    fn table(&self, key: &str, mapped: Option<&String>, default: &str) -> Result<String> {
+        let Some(mapped) = mapped else {
+            return Ok(default.to_string());
+        };
+        match self.tables.iter().find(|t| t.eq_ignore_ascii_case(mapped)) {
+            Some(table) => Ok(table.clone()),
+            None => Err(Error::schema(format!(
+                "schema mapping {} = \"{}\" names no table in {} (tables: {})",
+                key,
+                mapped,
+                self.db,
+                self.tables.join(", ")
+            ))),
+        }
+    }
+
+    /// The mapped column, which must exist in `table`, or the first of `candidates` it has.
+    fn column(
+        &self,
+        table: &str,
+        columns: &[String],
+        key: &str,
+        mapped: Option<&String>,
+        candidates: &[&str],
+    ) -> Result<Option<String>> {
+        let Some(mapped) = mapped else {
+            return Ok(find_column(columns, candidates).map(str::to_string));
+        };
+        match columns.iter().find(|c| c.eq_ignore_ascii_case(mapped)) {
+            Some(column) => Ok(Some(column.clone())),
+            None => Err(Error::schema(format!(
+                "schema mapping {} = \"{}\" is not a column of {} in {} (columns: {})",
+                key,
+                mapped,
+                table,
+                self.db,
+                columns.join(", ")
+            ))),
+        }
+    }
+
+    fn lookup(
+        &self,
+        prefix: &str,
+        mapping: &LookupMapping,
+        default_table: &str,
+        id_candidates: &[&str],
+        name_candidates: &[&str],
+    ) -> Result<Option<Lookup>> {
+        let key = |field: &str| format!("{}.{}", prefix, field);
+        let table = self.table(&key("table"), mapping.table.as_ref(), default_table)?;
+        let columns = table_columns(self.conn, self.db, &table)?;
+        let id = self.column(
+            &table,
+            &columns,
+            &key("id"),
+            mapping.id.as_ref(),
+            id_candidates,
+        )?;
+        let name = self.column(
+            &table,
+            &columns,
+            &key("name"),
+            mapping.name.as_ref(),
+            name_candidates,
+        )?;
+        Ok(id.zip(name).map(|(id, name)| Lookup { table, id, name }))
+    }
+}
+
+/// Apply `mapping` to the database, checking that every name it gives exists, and detect the
+/// rest.
+pub(crate) fn resolve_schema(
+    conn: &Connection,
+    db: &str,
+    mapping: &SchemaMapping,
+) -> Result<ResolvedSchema> {
+    let resolver = Resolver {
+        conn,
+        db,
+        tables: table_names(conn, db)?,
+    };
+
+    let s = &mapping.systems;
+    let systems = resolver.table("systems.table", s.table.as_ref(), "SolarSystems")?;
+    let columns = table_columns(conn, db, &systems)?;
+    let column = |key: &str, mapped: Option<&String>, candidates: &[&str]| {
+        resolver.column(&systems, &columns, key, mapped, candidates)
+    };
+    let system_id = column("systems.id", s.id.as_ref(), &["solarSystemId"])?;
+    let system_name = column("systems.name", s.name.as_ref(), &["name"])?;
+    // Region, constellation and coordinate columns are optional; older extracts lack them.
+    let region = column("systems.region", s.region.as_ref(), &["regionID"])?;
+    let constellation = column(
+        "systems.constellation",
+        s.constellation.as_ref(),
+        &["constellationID"],
+    )?;
+    let coordinates = match (
+        column("systems.x", s.x.as_ref(), &["centerX", "x"])?,
+        column("systems.y", s.y.as_ref(), &["centerY", "y"])?,
+        column("systems.z", s.z.as_ref(), &["centerZ", "z"])?,
+    ) {
+        (Some(x), Some(y), Some(z)) => Some([x, y, z]),
+        _ => None,
+    };
+
+    let j = &mapping.jumps;
+    let jumps = resolver.table("jumps.table", j.table.as_ref(), "Jumps")?;
+    let jump_columns = table_columns(conn, db, &jumps)?;
+    let jump_column = |key: &str, mapped: Option<&String>, candidate: &str| {
+        resolver.column(&jumps, &jump_columns, key, mapped, &[candidate])
+    };
+    let jump_from = jump_column("jumps.from", j.from.as_ref(), "fromSystemId")?;
+    let jump_to = jump_column("jumps.to", j.to.as_ref(), "toSystemId")?;
+
+    Ok(ResolvedSchema {
+        system_id: system_id.unwrap_or_else(|| "solarSystemId".to_string()),
+        system_name: system_name.unwrap_or_else(|| "name".to_string()),
+        systems,
+        region,
+        constellation,
+        coordinates,
+        jump_from: jump_from.unwrap_or_else(|| "fromSystemId".to_string()),
+        jump_to: jump_to.unwrap_or_else(|| "toSystemId".to_string()),
+        jumps,
+        region_names: resolver.lookup(
+            "regions",
+            &mapping.regions,
+            "Regions",
+            &["regionID"],
+            &["regionName", "name"],
+        )?,
+        constellation_names: resolver.lookup(
+            "constellations",
+            &mapping.constellations,
+            "Constellations",
+            &["constellationID"],
+            &["constellationName", "name"],
+        )?,
+    })
+}
+
+/// `id -> name` from a lookup table such as `Regions`; empty when the dataset has none.
+fn load_names(conn: &Connection, lookup: Option<&Lookup>) -> Result<HashMap<i64, String>> {
+    let Some(lookup) = lookup else {
+        return Ok(HashMap::new());
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, {} FROM {}",
+        quote_identifier(&lookup.id),
+        quote_identifier(&lookup.name),
+        quote_identifier(&lookup.table)
+    ))?;
+    let names = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(names)
+}
+
+/// Open the database at `path` read-only. Unlike `Connection::open`, a missing file is an
+/// error instead of a new empty database, and locking or permission problems are reported as
+/// such.
+pub fn open_dataset(path: &Path) -> Result<Connection> {
+    if !path.exists() {
+        return Err(Error::io(format!(
+            "dataset {} does not exist",
+            path.display()
+        )));
+    }
+    // Surface permission problems as I/O errors rather than SQLite's generic "unable to open".
+    fs::File::open(path).context(ErrorKind::Io, || {
+        format!("cannot read dataset {}", path.display())
+    })?;
+
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|err| open_error(path, err))?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|err| open_error(path, err))?;
+    // SQLite opens lazily; read the schema now so a locked or corrupt file fails here.
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))
+        .map_err(|err| open_error(path, err))?;
+    Ok(conn)
+}
+
+fn open_error(path: &Path, err: rusqlite::Error) -> Error {
+    let message = match err.sqlite_error_code() {
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked) => {
+            format!("{} is locked by another process", path.display())
+        }
+        Some(ErrorCode::PermissionDenied | ErrorCode::CannotOpen) => {
+            format!("cannot open {} for reading", path.display())
+        }
+        Some(ErrorCode::NotADatabase) => format!("{} is not a SQLite database", path.display()),
+        _ => format!("failed to open SQLite database {}", path.display()),
+    };
+    Error::new(ErrorKind::Database, message, Some(err.into()))
+}
+
+pub fn load_starmap(path: &Path) -> Result<(Vec<System>, Vec<Jump>)> {
+    load_starmap_with(path, &SchemaMapping::default())
+}
+
+/// [`load_starmap`] with the tables and columns named in `mapping` taking precedence over
+/// detection.
+pub fn load_starmap_with(path: &Path, mapping: &SchemaMapping) -> Result<(Vec<System>, Vec<Jump>)> {
+    let conn = open_dataset(path)?;
+    read_starmap(&conn, &path.display().to_string(), mapping)
+}
+
+/// [`load_starmap`] over a connection the caller already holds, such as an in-memory database.
+pub fn load_starmap_from_connection(conn: &Connection) -> Result<(Vec<System>, Vec<Jump>)> {
+    load_starmap_from_connection_with(conn, &SchemaMapping::default())
+}
+
+/// [`load_starmap_from_connection`] with a [`SchemaMapping`], like [`load_starmap_with`].
+pub fn load_starmap_from_connection_with(
+    conn: &Connection,
+    mapping: &SchemaMapping,
+) -> Result<(Vec<System>, Vec<Jump>)> {
+    read_starmap(conn, &connection_name(conn), mapping)
+}
+
+/// How error messages refer to the database behind `conn`.
+pub(crate) fn connection_name(conn: &Connection) -> String {
+    match conn.path() {
+        Some(path) if !path.is_empty() => path.to_string(),
+        _ => "in-memory database".to_string(),
+    }
+}
 
+fn read_starmap(
+    conn: &Connection,
+    db: &str,
+    mapping: &SchemaMapping,
+) -> Result<(Vec<System>, Vec<Jump>)> {
+    let schema = resolve_schema(conn, db, mapping)?;
     let mut systems = Vec::new();
     let mut jumps = Vec::new();
 
     {
-        let mut stmt = conn.prepare(
-            "SELECT solarSystemId, name
-             FROM SolarSystems",
-        )?;
+        let optional = |column: &Option<String>| {
+            column
+                .as_deref()
+                .map_or_else(|| "NULL".to_string(), quote_identifier)
+        };
+        let coordinate_columns = match &schema.coordinates {
+            Some(columns) => columns
+                .iter()
+                .map(|c| quote_identifier(c))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => "NULL, NULL, NULL".to_string(),
+        };
+        let regions = load_names(conn, schema.region_names.as_ref())?;
+        let constellations = load_names(conn, schema.constellation_names.as_ref())?;
+
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {}, {}, {}, {}, {}
+             FROM {}",
+                quote_identifier(&schema.system_id),
+                quote_identifier(&schema.system_name),
+                optional(&schema.constellation),
+                optional(&schema.region),
+                coordinate_columns,
+                quote_identifier(&schema.systems)
+            ))
+            .context(ErrorKind::Schema, || {
+                format!("{} has no readable {} table", db, schema.systems)
+            })?;
         let mut rows = stmt.query([])?;
         while let Some(row) = rows.next()? {
+            let constellation_id: Option<i64> = row.get(2)?;
+            let region_id: Option<i64> = row.get(3)?;
+            let position = match (row.get(4)?, row.get(5)?, row.get(6)?) {
+                (Some(x), Some(y), Some(z)) => Some(Position { x, y, z }),
+                _ => None,
+            };
             systems.push(System {
                 id: row.get(0)?,
                 name: row.get(1)?,
+                region_id,
+                region: region_id.and_then(|id| regions.get(&id).cloned()),
+                constellation_id,
+                constellation: constellation_id.and_then(|id| constellations.get(&id).cloned()),
+                position,
             });
         }
     }
 
     {
-        let mut stmt = conn.prepare(
-            "SELECT fromSystemId, toSystemId
-             FROM Jumps",
-        )?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {}, {}
+             FROM {}",
+                quote_identifier(&schema.jump_from),
+                quote_identifier(&schema.jump_to),
+                quote_identifier(&schema.jumps)
+            ))
+            .context(ErrorKind::Schema, || {
+                format!("{} has no readable {} table", db, schema.jumps)
+            })?;
         let mut rows = stmt.query([])?;
         while let Some(row) = rows.next()? {
             jumps.push(Jump {
@@ -50,3 +447,77 @@ pub fn load_starmap(path: &std::path::Path) -> Result<(Vec<System>, Vec<Jump>)>
 
     Ok((systems, jumps))
 }
+
+/// Check that `path` is a SQLite database with the tables and columns [`load_starmap`] reads.
+pub fn validate_dataset(path: &Path) -> Result<()> {
+    validate_dataset_with(path, &SchemaMapping::default())
+}
+
+/// [`validate_dataset`] for the tables and columns [`load_starmap_with`] reads through `mapping`.
+pub fn validate_dataset_with(path: &Path, mapping: &SchemaMapping) -> Result<()> {
+    let conn = open_dataset(path)?;
+    let db = path.display().to_string();
+    let schema = resolve_schema(&conn, &db, mapping)?;
+    match schema_problems(&conn, &db, &schema)?.into_iter().next() {
+        Some(problem) => Err(Error::schema(problem)),
+        None => Ok(()),
+    }
+}
+
+/// Every required table or column of `schema` missing from the dataset, as messages naming
+/// `db`.
+pub(crate) fn schema_problems(
+    conn: &Connection,
+    db: &str,
+    schema: &ResolvedSchema,
+) -> Result<Vec<String>> {
+    let mut problems = Vec::new();
+    for (table, columns) in [
+        (&schema.systems, [&schema.system_id, &schema.system_name]),
+        (&schema.jumps, [&schema.jump_from, &schema.jump_to]),
+    ] {
+        let present = table_columns(conn, db, table)?;
+        if present.is_empty() {
+            problems.push(format!("{} has no {} table", db, table));
+            continue;
+        }
+        for column in columns {
+            if find_column(&present, &[column.as_str()]).is_none() {
+                problems.push(format!(
+                    "{} table {} is missing column {}",
+                    db, table, column
+                ));
+            }
+        }
+    }
+    Ok(problems)
+}
+
+/// Hex-encoded SHA-256 digest of a file.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).context(ErrorKind::Io, || {
+        format!("failed to open {}", path.display())
+    })?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buffer).context(ErrorKind::Io, || {
+            format!("failed to read {}", path.display())
+        })?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Index of the system called `name`.
+pub fn find_system(systems: &[System], name: &str) -> Result<usize> {
+    systems
+        .iter()
+        .position(|s| s.name == name)
+        .ok_or_else(|| Error::UnknownSystem {
+            name: name.to_string(),
+        })
+}
@@ -0,0 +1,152 @@
+use crate::db::System;
+use crate::starmap::Starmap;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A system as it appears in a [`StarmapDiff`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SystemRef {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RenamedSystem {
+    pub id: i64,
+    pub old_name: String,
+    pub new_name: String,
+}
+
+/// An undirected jump, named as in the star map it appears in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JumpRef {
+    pub from: SystemRef,
+    pub to: SystemRef,
+}
+
+/// Where a system sits in the region/constellation hierarchy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Membership {
+    pub region_id: Option<i64>,
+    pub region: Option<String>,
+    pub constellation_id: Option<i64>,
+    pub constellation: Option<String>,
+}
+
+impl Membership {
+    fn of(system: &System) -> Self {
+        Membership {
+            region_id: system.region_id,
+            region: system.region.clone(),
+            constellation_id: system.constellation_id,
+            constellation: system.constellation.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MembershipChange {
+    pub system: SystemRef,
+    pub old: Membership,
+    pub new: Membership,
+}
+
+/// What changed between two star maps. Systems are matched by id, so a system whose id stays
+/// the same but whose name changes is reported as renamed rather than removed and added.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StarmapDiff {
+    pub added_systems: Vec<SystemRef>,
+    pub removed_systems: Vec<SystemRef>,
+    pub renamed_systems: Vec<RenamedSystem>,
+    pub added_jumps: Vec<JumpRef>,
+    pub removed_jumps: Vec<JumpRef>,
+    pub membership_changes: Vec<MembershipChange>,
+}
+
+impl StarmapDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_systems.is_empty()
+            && self.removed_systems.is_empty()
+            && self.renamed_systems.is_empty()
+            && self.added_jumps.is_empty()
+            && self.removed_jumps.is_empty()
+            && self.membership_changes.is_empty()
+    }
+}
+
+fn by_id(starmap: &Starmap) -> BTreeMap<i64, &System> {
+    starmap.systems.iter().map(|s| (s.id, s)).collect()
+}
+
+/// Jumps as unordered id pairs, ignoring direction and duplicates.
+fn jump_set(starmap: &Starmap) -> BTreeSet<(i64, i64)> {
+    starmap
+        .jumps
+        .iter()
+        .map(|j| (j.from_id.min(j.to_id), j.from_id.max(j.to_id)))
+        .collect()
+}
+
+fn system_ref(systems: &BTreeMap<i64, &System>, id: i64) -> SystemRef {
+    SystemRef {
+        id,
+        name: systems
+            .get(&id)
+            .map_or_else(|| format!("#{}", id), |s| s.name.clone()),
+    }
+}
+
+/// Compare `old` with `new`. Results are ordered by system id.
+pub fn diff_starmaps(old: &Starmap, new: &Starmap) -> StarmapDiff {
+    let old_systems = by_id(old);
+    let new_systems = by_id(new);
+    let mut diff = StarmapDiff::default();
+
+    for (id, system) in &new_systems {
+        match old_systems.get(id) {
+            None => diff.added_systems.push(system_ref(&new_systems, *id)),
+            Some(previous) => {
+                if previous.name != system.name {
+                    diff.renamed_systems.push(RenamedSystem {
+                        id: *id,
+                        old_name: previous.name.clone(),
+                        new_name: system.name.clone(),
+                    });
+                }
+                let (old_membership, new_membership) =
+                    (Membership::of(previous), Membership::of(system));
+                if old_membership != new_membership {
+                    diff.membership_changes.push(MembershipChange {
+                        system: system_ref(&new_systems, *id),
+                        old: old_membership,
+                        new: new_membership,
+                    });
+                }
+            }
+        }
+    }
+    for id in old_systems.keys() {
+        if !new_systems.contains_key(id) {
+            diff.removed_systems.push(system_ref(&old_systems, *id));
+        }
+    }
+
+    let old_jumps = jump_set(old);
+    let new_jumps = jump_set(new);
+    diff.added_jumps = new_jumps
+        .difference(&old_jumps)
+        .map(|&(a, b)| JumpRef {
+            from: system_ref(&new_systems, a),
+            to: system_ref(&new_systems, b),
+        })
+        .collect();
+    diff.removed_jumps = old_jumps
+        .difference(&new_jumps)
+        .map(|&(a, b)| JumpRef {
+            from: system_ref(&old_systems, a),
+            to: system_ref(&old_systems, b),
+        })
+        .collect();
+
+    diff
+}
@@ -0,0 +1,170 @@
+use std::error::Error as StdError;
+
+type BoxError = Box<dyn StdError + Send + Sync + 'static>;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The category of an [`Error`], for callers that branch on what went wrong rather than on
+/// the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Querying the releases API or fetching an asset failed.
+    Download,
+    /// An archive could not be read or did not contain the expected database.
+    Extract,
+    /// The database does not have a supported schema.
+    Schema,
+    /// SQLite failed to open or query the database.
+    Database,
+    /// A system name did not match any system in the star map.
+    UnknownSystem,
+    /// No path connects the requested systems.
+    NoRoute,
+    /// A filesystem operation outside download/extraction failed.
+    Io,
+    /// A caller-supplied value (pattern, name, option) is invalid.
+    InvalidInput,
+}
+
+/// Errors returned by `evefrontier-lib`.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("{message}")]
+    Download {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    #[error("{message}")]
+    Extract {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    #[error("{message}")]
+    Schema {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    #[error("{message}")]
+    Database {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    #[error("unknown system '{name}'")]
+    UnknownSystem { name: String },
+    #[error("no route from {from} to {to}")]
+    NoRoute { from: String, to: String },
+    #[error("{message}")]
+    Io {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    #[error("{message}")]
+    InvalidInput {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    /// An error of a kind whose own variant has structured fields (`UnknownSystem`, `NoRoute`)
+    /// that was built from a plain message instead.
+    #[error("{message}")]
+    Other {
+        kind: ErrorKind,
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+}
+
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Download { .. } => ErrorKind::Download,
+            Error::Extract { .. } => ErrorKind::Extract,
+            Error::Schema { .. } => ErrorKind::Schema,
+            Error::Database { .. } => ErrorKind::Database,
+            Error::UnknownSystem { .. } => ErrorKind::UnknownSystem,
+            Error::NoRoute { .. } => ErrorKind::NoRoute,
+            Error::Io { .. } => ErrorKind::Io,
+            Error::InvalidInput { .. } => ErrorKind::InvalidInput,
+            Error::Other { kind, .. } => *kind,
+        }
+    }
+
+    /// Build an error of `kind` carrying `message` and an optional underlying cause.
+    ///
+    /// `UnknownSystem` and `NoRoute` carry structured fields, so prefer constructing those
+    /// variants directly; given here they become [`Error::Other`].
+    pub(crate) fn new(
+        kind: ErrorKind,
+        message: impl Into<String>,
+        source: Option<BoxError>,
+    ) -> Self {
+        let message = message.into();
+        match kind {
+            ErrorKind::Download => Error::Download { message, source },
+            ErrorKind::Extract => Error::Extract { message, source },
+            ErrorKind::Schema => Error::Schema { message, source },
+            ErrorKind::Database => Error::Database { message, source },
+            ErrorKind::Io => Error::Io { message, source },
+            ErrorKind::InvalidInput => Error::InvalidInput { message, source },
+            ErrorKind::UnknownSystem | ErrorKind::NoRoute => Error::Other {
+                kind,
+                message,
+                source,
+            },
+        }
+    }
+
+    #[cfg(feature = "download")]
+    pub(crate) fn download(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Download, message, None)
+    }
+
+    #[cfg(feature = "download")]
+    pub(crate) fn extract(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Extract, message, None)
+    }
+
+    pub(crate) fn schema(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Schema, message, None)
+    }
+
+    pub(crate) fn io(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Io, message, None)
+    }
+
+    pub(crate) fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::InvalidInput, message, None)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::new(
+            ErrorKind::Database,
+            "database query failed",
+            Some(err.into()),
+        )
+    }
+}
+
+/// Attach a category and a message to a foreign error, in the spirit of `anyhow::Context`.
+pub(crate) trait ResultExt<T> {
+    fn context<M: Into<String>>(self, kind: ErrorKind, message: impl FnOnce() -> M) -> Result<T>;
+}
+
+impl<T, E> ResultExt<T> for std::result::Result<T, E>
+where
+    E: Into<BoxError>,
+{
+    fn context<M: Into<String>>(self, kind: ErrorKind, message: impl FnOnce() -> M) -> Result<T> {
+        self.map_err(|err| Error::new(kind, message(), Some(err.into())))
+    }
+}
@@ -0,0 +1,256 @@
+//! Serializers that write the star map, or part of it, for external graph tools: Graphviz DOT,
+//! GraphML and GEXF (Gephi). Each node carries the system id, name, region, constellation and,
+//! when the dataset has them, coordinates in metres.
+
+use crate::db::System;
+use crate::error::{Error, ErrorKind, Result, ResultExt};
+use crate::graph::StarGraph;
+use crate::starmap::Starmap;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use std::collections::{BTreeSet, VecDeque};
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Dot,
+    GraphMl,
+    Gexf,
+}
+
+impl ExportFormat {
+    /// The format conventionally stored under file extension `ext`.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "dot" | "gv" => Some(ExportFormat::Dot),
+            "graphml" => Some(ExportFormat::GraphMl),
+            "gexf" => Some(ExportFormat::Gexf),
+            _ => None,
+        }
+    }
+}
+
+/// Indices of the systems in `region`, matched by region name or id.
+pub fn region_systems(systems: &[System], region: &str) -> Result<Vec<usize>> {
+    let selected: Vec<usize> = systems
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| {
+            s.region.as_deref() == Some(region)
+                || s.region_id.is_some_and(|id| id.to_string() == region)
+        })
+        .map(|(i, _)| i)
+        .collect();
+    if selected.is_empty() {
+        return Err(Error::invalid_input(format!(
+            "no systems in region '{}'",
+            region
+        )));
+    }
+    Ok(selected)
+}
+
+/// Indices of the systems at most `jumps` jumps from `start`, including `start`.
+pub fn neighbourhood(graph: &StarGraph, start: usize, jumps: usize) -> Vec<usize> {
+    let mut distance = vec![usize::MAX; graph.node_count()];
+    let mut queue = VecDeque::from([start]);
+    distance[start] = 0;
+    while let Some(idx) = queue.pop_front() {
+        if distance[idx] == jumps {
+            continue;
+        }
+        for next in graph.neighbors(NodeIndex::new(idx)) {
+            if distance[next.index()] == usize::MAX {
+                distance[next.index()] = distance[idx] + 1;
+                queue.push_back(next.index());
+            }
+        }
+    }
+    (0..distance.len())
+        .filter(|&i| distance[i] != usize::MAX)
+        .collect()
+}
+
+/// Write the systems in `nodes` (all systems when `None`) and the jumps between them.
+pub fn export_starmap(
+    writer: &mut dyn Write,
+    starmap: &Starmap,
+    nodes: Option<&[usize]>,
+    format: ExportFormat,
+) -> Result<()> {
+    let nodes: BTreeSet<usize> = match nodes {
+        Some(nodes) => nodes.iter().copied().collect(),
+        None => (0..starmap.systems.len()).collect(),
+    };
+    let edges: Vec<(usize, usize)> = starmap
+        .graph
+        .edge_references()
+        .map(|e| (e.source().index(), e.target().index()))
+        .filter(|(a, b)| nodes.contains(a) && nodes.contains(b))
+        .collect();
+    let systems: Vec<&System> = nodes.iter().map(|&i| &starmap.systems[i]).collect();
+    let ids = |(a, b): (usize, usize)| (starmap.systems[a].id, starmap.systems[b].id);
+    let edges: Vec<(i64, i64)> = edges.into_iter().map(ids).collect();
+
+    match format {
+        ExportFormat::Dot => write_dot(writer, &systems, &edges),
+        ExportFormat::GraphMl => write_graphml(writer, &systems, &edges),
+        ExportFormat::Gexf => write_gexf(writer, &systems, &edges),
+    }
+    .context(ErrorKind::Io, || "failed to write export")
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Attribute name/value pairs shared by all formats, skipping values the dataset lacks.
+fn attributes(system: &System) -> Vec<(&'static str, String)> {
+    let mut attrs = vec![("name", system.name.clone())];
+    if let Some(id) = system.region_id {
+        attrs.push(("region_id", id.to_string()));
+    }
+    if let Some(region) = &system.region {
+        attrs.push(("region", region.clone()));
+    }
+    if let Some(id) = system.constellation_id {
+        attrs.push(("constellation_id", id.to_string()));
+    }
+    if let Some(constellation) = &system.constellation {
+        attrs.push(("constellation", constellation.clone()));
+    }
+    if let Some(p) = system.position {
+        attrs.push(("x", p.x.to_string()));
+        attrs.push(("y", p.y.to_string()));
+        attrs.push(("z", p.z.to_string()));
+    }
+    attrs
+}
+
+/// `(key, type)` for every attribute [`attributes`] can produce, in GraphML/GEXF type names.
+const ATTRIBUTE_TYPES: [(&str, &str); 8] = [
+    ("name", "string"),
+    ("region_id", "long"),
+    ("region", "string"),
+    ("constellation_id", "long"),
+    ("constellation", "string"),
+    ("x", "double"),
+    ("y", "double"),
+    ("z", "double"),
+];
+
+fn write_dot(w: &mut dyn Write, systems: &[&System], edges: &[(i64, i64)]) -> std::io::Result<()> {
+    writeln!(w, "graph starmap {{")?;
+    for system in systems {
+        let attrs: Vec<String> = std::iter::once(("label", system.name.clone()))
+            .chain(attributes(system))
+            .map(|(k, v)| format!("{}=\"{}\"", k, dot_escape(&v)))
+            .collect();
+        writeln!(w, "  \"{}\" [{}];", system.id, attrs.join(", "))?;
+    }
+    for (a, b) in edges {
+        writeln!(w, "  \"{}\" -- \"{}\";", a, b)?;
+    }
+    writeln!(w, "}}")
+}
+
+fn write_graphml(
+    w: &mut dyn Write,
+    systems: &[&System],
+    edges: &[(i64, i64)],
+) -> std::io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    for (key, ty) in ATTRIBUTE_TYPES {
+        writeln!(
+            w,
+            r#"  <key id="{0}" for="node" attr.name="{0}" attr.type="{1}"/>"#,
+            key, ty
+        )?;
+    }
+    writeln!(w, r#"  <graph id="starmap" edgedefault="undirected">"#)?;
+    for system in systems {
+        writeln!(w, r#"    <node id="{}">"#, system.id)?;
+        for (key, value) in attributes(system) {
+            writeln!(
+                w,
+                r#"      <data key="{}">{}</data>"#,
+                key,
+                xml_escape(&value)
+            )?;
+        }
+        writeln!(w, "    </node>")?;
+    }
+    for (a, b) in edges {
+        writeln!(w, r#"    <edge source="{}" target="{}"/>"#, a, b)?;
+    }
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</graphml>")
+}
+
+fn write_gexf(w: &mut dyn Write, systems: &[&System], edges: &[(i64, i64)]) -> std::io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">"#
+    )?;
+    writeln!(w, r#"  <graph mode="static" defaultedgetype="undirected">"#)?;
+    writeln!(w, r#"    <attributes class="node">"#)?;
+    for (key, ty) in ATTRIBUTE_TYPES.iter().skip(1) {
+        writeln!(
+            w,
+            r#"      <attribute id="{0}" title="{0}" type="{1}"/>"#,
+            key, ty
+        )?;
+    }
+    writeln!(w, "    </attributes>")?;
+    writeln!(w, "    <nodes>")?;
+    for system in systems {
+        writeln!(
+            w,
+            r#"      <node id="{}" label="{}">"#,
+            system.id,
+            xml_escape(&system.name)
+        )?;
+        writeln!(w, "        <attvalues>")?;
+        for (key, value) in attributes(system).into_iter().skip(1) {
+            writeln!(
+                w,
+                r#"          <attvalue for="{}" value="{}"/>"#,
+                key,
+                xml_escape(&value)
+            )?;
+        }
+        writeln!(w, "        </attvalues>")?;
+        if let Some(p) = system.position {
+            writeln!(
+                w,
+                r#"        <viz:position x="{}" y="{}" z="{}"/>"#,
+                p.x, p.y, p.z
+            )?;
+        }
+        writeln!(w, "      </node>")?;
+    }
+    writeln!(w, "    </nodes>")?;
+    writeln!(w, "    <edges>")?;
+    for (i, (a, b)) in edges.iter().enumerate() {
+        writeln!(
+            w,
+            r#"      <edge id="{}" source="{}" target="{}"/>"#,
+            i, a, b
+        )?;
+    }
+    writeln!(w, "    </edges>")?;
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</gexf>")
+}
@@ -1,6 +1,9 @@
-use anyhow::{anyhow, Context, Result};
-use dirs::cache_dir;
-use indicatif::{ProgressBar, ProgressStyle};
+use crate::cache;
+use crate::config::SchemaMapping;
+use crate::db;
+use crate::error::{Error, ErrorKind, Result, ResultExt};
+use crate::install::{self, ArchiveKind};
+use crate::progress::{ProgressEvent, ProgressReporter, SilentProgress};
 use reqwest::blocking::Client;
 use serde::Deserialize;
 use std::path::Path;
@@ -9,12 +12,14 @@ use std::{
     io::{Read, Write},
     path::PathBuf,
 };
-use zip::ZipArchive;
+
+const LATEST_RELEASE_API: &str =
+    "https://api.github.com/repos/Scetrov/evefrontier_datasets/releases/latest";
+pub(crate) const USER_AGENT: &str = "evefrontier-pathfinder (github.com/Scetrov)";
 
 #[derive(Deserialize)]
-struct Release {
-    #[serde(rename = "tag_name")]
-    _tag_name: String,
+pub(crate) struct Release {
+    tag_name: String,
     assets: Vec<Asset>,
 }
 
@@ -22,49 +27,291 @@ struct Release {
 struct Asset {
     browser_download_url: String,
     name: String,
+    #[serde(default)]
+    size: u64,
+}
+
+/// A downloadable asset attached to a dataset release.
+#[derive(Debug, Clone)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub size: u64,
+    pub download_url: String,
+}
+
+/// The release tag and assets of the latest `evefrontier_datasets` release.
+#[derive(Debug, Clone)]
+pub struct ReleaseInfo {
+    pub tag: String,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+impl From<Release> for ReleaseInfo {
+    fn from(release: Release) -> Self {
+        ReleaseInfo {
+            tag: release.tag_name,
+            assets: release
+                .assets
+                .into_iter()
+                .map(|a| ReleaseAsset {
+                    name: a.name,
+                    size: a.size,
+                    download_url: a.browser_download_url,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Where the downloader looks for releases and keeps downloaded assets.
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// URL of the GitHub API endpoint describing the latest release.
+    pub release_api: String,
+    /// Directory release assets are cached in; `None` uses [`default_cache_root`].
+    ///
+    /// [`default_cache_root`]: crate::cache::default_cache_root
+    pub cache_root: Option<PathBuf>,
+    /// Mapping the installed DB is validated with, for datasets with renamed tables or columns.
+    pub schema: SchemaMapping,
+    /// Download the asset again and reinstall it even when the target already has a DB.
+    pub force: bool,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        DownloadOptions {
+            release_api: LATEST_RELEASE_API.to_string(),
+            cache_root: None,
+            schema: SchemaMapping::default(),
+            force: false,
+        }
+    }
+}
+
+impl DownloadOptions {
+    pub(crate) fn cache_root(&self) -> Result<PathBuf> {
+        match &self.cache_root {
+            Some(root) => Ok(root.clone()),
+            None => cache::default_cache_root(),
+        }
+    }
+}
+
+/// A name pattern used to pick a release asset or an entry inside an archive.
+#[derive(Debug, Clone)]
+pub enum NamePattern {
+    Glob(glob::Pattern),
+    Regex(regex::Regex),
+}
+
+impl NamePattern {
+    pub fn glob(pattern: &str) -> Result<Self> {
+        let pattern = glob::Pattern::new(pattern).context(ErrorKind::InvalidInput, || {
+            format!("invalid glob pattern '{}'", pattern)
+        })?;
+        Ok(NamePattern::Glob(pattern))
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self> {
+        let pattern = regex::Regex::new(pattern).context(ErrorKind::InvalidInput, || {
+            format!("invalid regular expression '{}'", pattern)
+        })?;
+        Ok(NamePattern::Regex(pattern))
+    }
+
+    /// Match against the full name, or against the final path component for archive entries
+    /// stored in subdirectories.
+    pub fn matches(&self, name: &str) -> bool {
+        let base = name.rsplit('/').next().unwrap_or(name);
+        match self {
+            NamePattern::Glob(p) => p.matches(name) || p.matches(base),
+            NamePattern::Regex(r) => r.is_match(name) || r.is_match(base),
+        }
+    }
+}
+
+impl std::fmt::Display for NamePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NamePattern::Glob(p) => write!(f, "{}", p.as_str()),
+            NamePattern::Regex(r) => write!(f, "/{}/", r.as_str()),
+        }
+    }
+}
+
+/// Chooses which release asset to download and which `.db` entry to extract from it.
+///
+/// With no patterns set, the first dataset asset is used and archive entries containing
+/// `c3e6` are preferred. A pattern must match exactly one candidate.
+#[derive(Debug, Clone, Default)]
+pub struct DatasetSelector {
+    pub asset: Option<NamePattern>,
+    pub entry: Option<NamePattern>,
 }
 
-// (moved to the Option-taking implementation further down)
+impl DatasetSelector {
+    /// The dataset asset among `assets` the selector picks.
+    pub fn select_asset<'a>(&self, assets: &'a [ReleaseAsset]) -> Result<&'a ReleaseAsset> {
+        let candidates: Vec<&ReleaseAsset> = assets
+            .iter()
+            .filter(|a| ArchiveKind::from_name(&a.name).is_some())
+            .collect();
+
+        let Some(pattern) = &self.asset else {
+            return candidates.first().copied().ok_or_else(|| {
+                Error::download("No suitable asset (.zip or .db) found in latest release")
+            });
+        };
+
+        let matched: Vec<&ReleaseAsset> = candidates
+            .into_iter()
+            .filter(|a| pattern.matches(&a.name))
+            .collect();
+        match matched.as_slice() {
+            [one] => Ok(one),
+            [] => Err(Error::download(format!(
+                "No dataset asset matches '{}'; available assets: {}",
+                pattern,
+                join_names(assets.iter().map(|a| a.name.as_str()))
+            ))),
+            many => Err(Error::download(format!(
+                "Asset pattern '{}' is ambiguous, it matches: {}",
+                pattern,
+                join_names(many.iter().map(|a| a.name.as_str()))
+            ))),
+        }
+    }
+
+    /// The index of the `.db` entry among an archive's `entries` the selector picks.
+    pub fn select_entry(&self, entries: &[String]) -> Result<usize> {
+        let Some(pattern) = &self.entry else {
+            let mut candidate_index: Option<usize> = None;
+            for (i, name) in entries.iter().enumerate() {
+                if name.ends_with(".db") {
+                    if name.to_lowercase().contains("c3e6") {
+                        candidate_index = Some(i);
+                        break;
+                    }
+                    if candidate_index.is_none() {
+                        candidate_index = Some(i);
+                    }
+                }
+            }
+            return candidate_index
+                .ok_or_else(|| Error::extract("No .db file found inside archive"));
+        };
+
+        let matched: Vec<usize> = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| name.ends_with(".db") && pattern.matches(name))
+            .map(|(i, _)| i)
+            .collect();
+        match matched.as_slice() {
+            [one] => Ok(*one),
+            [] => Err(Error::extract(format!(
+                "No .db entry inside the archive matches '{}'; entries: {}",
+                pattern,
+                join_names(entries.iter().map(String::as_str))
+            ))),
+            many => Err(Error::extract(format!(
+                "Entry pattern '{}' is ambiguous, it matches: {}",
+                pattern,
+                join_names(many.iter().map(|&i| entries[i].as_str()))
+            ))),
+        }
+    }
+}
+
+fn join_names<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    names.collect::<Vec<_>>().join(", ")
+}
 
-fn download_asset(client: &Client, url: &str, dest: &PathBuf) -> Result<()> {
-    eprintln!("Downloading {}", url);
+fn download_asset(
+    client: &Client,
+    url: &str,
+    dest: &Path,
+    progress: &dyn ProgressReporter,
+) -> Result<()> {
     let mut resp = client
         .get(url)
-        .header("User-Agent", "evefrontier-pathfinder (github.com/Scetrov)")
+        .header("User-Agent", USER_AGENT)
         .send()
-        .context("failed to start download")?
+        .context(ErrorKind::Download, || "failed to start download")?
         .error_for_status()
-        .context("download request returned error")?;
+        .context(ErrorKind::Download, || "download request returned error")?;
 
-    let total_size = resp.content_length().unwrap_or(0);
+    let total_bytes = resp.content_length();
+    progress.report(&ProgressEvent::DownloadStarted { url, total_bytes });
 
-    let pb = ProgressBar::new(total_size);
-    pb.set_style(
-        ProgressStyle::with_template(
-            "[{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
-        )
-        .unwrap()
-        .progress_chars("=>-"),
-    );
-
-    let mut file = fs::File::create(dest)
-        .with_context(|| format!("failed to create file {}", dest.display()))?;
+    // Download to a temporary file then atomically rename it into place
+    let tmp = part_path(dest);
+    let mut file = fs::File::create(&tmp).context(ErrorKind::Download, || {
+        format!("failed to create file {}", tmp.display())
+    })?;
     let mut downloaded: u64 = 0;
     let mut buffer = [0u8; 8 * 1024];
     loop {
-        let n = resp.read(&mut buffer).context("download read error")?;
+        let n = resp
+            .read(&mut buffer)
+            .context(ErrorKind::Download, || "download read error")?;
         if n == 0 {
             break;
         }
-        file.write_all(&buffer[..n])?;
+        file.write_all(&buffer[..n])
+            .context(ErrorKind::Download, || {
+                format!("failed to write {}", tmp.display())
+            })?;
         downloaded += n as u64;
-        pb.set_position(downloaded);
+        progress.report(&ProgressEvent::DownloadProgress {
+            downloaded,
+            total_bytes,
+        });
     }
-    pb.finish_with_message("download complete");
+    drop(file);
+    fs::rename(&tmp, dest).context(ErrorKind::Download, || {
+        format!("failed to move download to {}", dest.display())
+    })?;
+    progress.report(&ProgressEvent::DownloadFinished { path: dest });
 
     Ok(())
 }
 
+/// The temporary file a download to `dest` is streamed into before being renamed.
+pub(crate) fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_os_string();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Download an arbitrary URL to `dest`, without going through the releases API.
+pub(crate) fn download_to(url: &str, dest: &Path, progress: &dyn ProgressReporter) -> Result<()> {
+    download_asset(&Client::new(), url, dest, progress)
+}
+
+fn fetch_latest_release(client: &Client, api_url: &str) -> Result<ReleaseInfo> {
+    let release: Release = client
+        .get(api_url)
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .context(ErrorKind::Download, || {
+            "failed to query GitHub releases API"
+        })?
+        .error_for_status()
+        .context(ErrorKind::Download, || "GitHub releases API returned error")?
+        .json()
+        .context(ErrorKind::Download, || {
+            "failed to parse GitHub releases JSON"
+        })?;
+    Ok(release.into())
+}
+
+/// Query the GitHub releases API for the latest dataset release and its assets.
+pub fn latest_release() -> Result<ReleaseInfo> {
+    fetch_latest_release(&Client::new(), LATEST_RELEASE_API)
+}
+
 /// Ensure we have a dataset available at `target_db`.
 ///
 /// If `target_db` is `Some(path)`, this function will ensure the file exists at that
@@ -72,117 +319,129 @@ fn download_asset(client: &Client, url: &str, dest: &PathBuf) -> Result<()> {
 /// the user's cache directory (same behavior as the previous implementation) and
 /// returns the path to the DB file.
 pub fn ensure_c3e6_dataset(target_db: Option<&Path>) -> Result<PathBuf> {
-    let client = Client::new();
-    let api = "https://api.github.com/repos/Scetrov/evefrontier_datasets/releases/latest";
-
-    let release: Release = client
-        .get(api)
-        .header("User-Agent", "evefrontier-pathfinder (github.com/Scetrov)")
-        .send()
-        .context("failed to query GitHub releases API")?
-        .error_for_status()
-        .context("GitHub releases API returned error")?
-        .json()
-        .context("failed to parse GitHub releases JSON")?;
-
-    let asset = release
-        .assets
-        .iter()
-        .find(|a| a.name.ends_with(".zip") || a.name.ends_with(".db"))
-        .ok_or_else(|| anyhow!("No suitable asset (.zip or .db) found in latest release"))?;
+    ensure_dataset(target_db, &DatasetSelector::default(), &SilentProgress)
+}
 
-    // Where will we place the final DB file?
-    let target_path = if let Some(p) = target_db {
-        p.to_path_buf()
-    } else {
-        cache_dir()
-            .ok_or_else(|| anyhow!("Could not determine user cache directory"))?
-            .join("evefrontier_datasets")
-            .join(&asset.name)
-    };
+/// Like [`ensure_c3e6_dataset`], but uses `selector` to pick the release asset and the
+/// database entry inside it, and reports download and extraction through `progress`.
+pub fn ensure_dataset(
+    target_db: Option<&Path>,
+    selector: &DatasetSelector,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf> {
+    ensure_dataset_with(target_db, selector, &DownloadOptions::default(), progress)
+}
 
-    if let Some(parent) = target_path.parent() {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("Failed to create data directory {}", parent.display()))?;
-    }
+/// Like [`ensure_dataset`], but queries `options.release_api` and caches assets under
+/// `options.cache_root`.
+///
+/// This uses a blocking HTTP client and must not be called from inside an async runtime; use
+/// `nonblocking::ensure_dataset_with` (feature `async`) there instead.
+pub fn ensure_dataset_with(
+    target_db: Option<&Path>,
+    selector: &DatasetSelector,
+    options: &DownloadOptions,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf> {
+    let client = Client::new();
+    let release = fetch_latest_release(&client, &options.release_api)?;
+    let asset = selector.select_asset(&release.assets)?;
 
-    if target_path.exists() {
-        eprintln!("Using existing DB: {}", target_path.display());
-        return Ok(target_path);
+    if let Some(existing) = existing_target(target_db, &asset.name, selector, options, progress)? {
+        return Ok(existing);
     }
 
-    // Download to a temporary file then atomically rename to target_path
-    let cache_root = cache_dir()
-        .ok_or_else(|| anyhow!("Could not determine user cache directory"))?
-        .join("evefrontier_datasets");
-    fs::create_dir_all(&cache_root).ok();
+    let cache_root = options.cache_root()?;
+    let _lock = cache::lock_cache(&cache_root)?;
     let cached_asset_path = cache_root.join(&asset.name);
+    if options.force {
+        cache::remove_asset(&cached_asset_path)?;
+    }
     if !cached_asset_path.exists() {
-        download_asset(&client, &asset.browser_download_url, &cached_asset_path)?;
+        download_asset(&client, &asset.download_url, &cached_asset_path, progress)?;
+        cache::record_download(&cached_asset_path, Some(&release.tag), &asset.download_url)?;
     } else {
-        eprintln!("Using cached asset: {}", cached_asset_path.display());
-    }
-
-    // If the cached asset is a DB, copy/rename it to target_path.
-    if cached_asset_path.extension().and_then(|e| e.to_str()) == Some("db") {
-        // copy to temp and rename
-        let tmp = target_path.with_extension("db.tmp");
-        fs::copy(&cached_asset_path, &tmp)
-            .with_context(|| format!("failed to copy DB to {}", tmp.display()))?;
-        fs::rename(&tmp, &target_path)
-            .with_context(|| format!("failed to move DB to {}", target_path.display()))?;
-        return Ok(target_path);
+        progress.report(&ProgressEvent::UsingCached {
+            path: &cached_asset_path,
+        });
+        cache::touch(&cached_asset_path)?;
     }
 
-    // Otherwise assume zip and extract
-    let extracted = extract_c3e6_from_zip(&cached_asset_path, &cache_root)?;
-    // Move extracted to target_path
-    let tmp = target_path.with_extension("db.tmp");
-    fs::copy(&extracted, &tmp)
-        .with_context(|| format!("failed to copy extracted DB to {}", tmp.display()))?;
-    fs::rename(&tmp, &target_path)
-        .with_context(|| format!("failed to move DB to {}", target_path.display()))?;
-    Ok(target_path)
+    finish_dataset(
+        &cached_asset_path,
+        target_db,
+        selector,
+        &options.schema,
+        progress,
+    )
 }
 
-// Compatibility shim: previous callers without args
-pub fn ensure_c3e6_dataset_default() -> Result<PathBuf> {
-    ensure_c3e6_dataset(None)
-}
-
-fn extract_c3e6_from_zip(zip_path: &PathBuf, out_dir: &PathBuf) -> Result<PathBuf> {
-    let file = fs::File::open(zip_path)
-        .with_context(|| format!("failed to open zip file {}", zip_path.display()))?;
-    let mut archive = ZipArchive::new(file)
-        .with_context(|| format!("failed to read zip archive {}", zip_path.display()))?;
-
-    let mut candidate_index: Option<usize> = None;
-    for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        let name = file.name().to_string();
-        if name.ends_with(".db") {
-            if name.to_lowercase().contains("c3e6") {
-                candidate_index = Some(i);
-                break;
-            }
-            if candidate_index.is_none() {
-                candidate_index = Some(i);
-            }
+/// Prepare the directory of `target_db` and return it if the DB installed there can be kept:
+/// not forced to reinstall, and when the selector has patterns, installed from the chosen
+/// `asset` and from an entry the entry pattern matches, as its `.meta.json` records.
+pub(crate) fn existing_target(
+    target_db: Option<&Path>,
+    asset: &str,
+    selector: &DatasetSelector,
+    options: &DownloadOptions,
+    progress: &dyn ProgressReporter,
+) -> Result<Option<PathBuf>> {
+    let Some(target) = target_db else {
+        return Ok(None);
+    };
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).context(ErrorKind::Io, || {
+            format!("Failed to create data directory {}", parent.display())
+        })?;
+    }
+    if !target.exists() || options.force {
+        return Ok(None);
+    }
+    if selector.asset.is_some() || selector.entry.is_some() {
+        let installed = cache::read_metadata(target)?.unwrap_or_default();
+        let same_asset = selector.asset.is_none() || installed.asset.as_deref() == Some(asset);
+        let same_entry = match (&selector.entry, &installed.entry) {
+            (None, _) => true,
+            (Some(pattern), Some(entry)) => pattern.matches(entry),
+            (Some(_), None) => false,
+        };
+        if !(same_asset && same_entry) {
+            return Ok(None);
         }
     }
+    progress.report(&ProgressEvent::UsingCached { path: target });
+    Ok(Some(target.to_path_buf()))
+}
 
-    let idx = candidate_index.ok_or_else(|| anyhow!("No .db file found inside zip"))?;
-    let mut db_file = archive.by_index(idx)?;
-    let db_name = db_file.name().rsplit('/').next().unwrap_or("c3e6.db");
-    let out_path = out_dir.join(db_name);
+/// Extract the DB from a cached asset and install it at `target_db`, or validate and return
+/// the cached copy when there is no target. Callers hold the cache lock.
+pub(crate) fn finish_dataset(
+    cached_asset_path: &Path,
+    target_db: Option<&Path>,
+    selector: &DatasetSelector,
+    mapping: &SchemaMapping,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf> {
+    let db_path = install::extract_db(cached_asset_path, selector, progress)?;
 
-    if out_path.exists() {
-        eprintln!("Using cached extracted DB: {}", out_path.display());
-        return Ok(out_path);
+    // Without an explicit target the cached copy is used in place.
+    match target_db {
+        Some(target) => {
+            // Release assets sit at the top of the cache under their own name.
+            let name = cached_asset_path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            install::install_db(&db_path, cached_asset_path, &name, target, mapping)
+        }
+        None => {
+            db::validate_dataset_with(&db_path, mapping)?;
+            Ok(db_path)
+        }
     }
+}
 
-    eprintln!("Extracting {} to {}", db_file.name(), out_path.display());
-    let mut out = fs::File::create(&out_path)?;
-    std::io::copy(&mut db_file, &mut out)?;
-    Ok(out_path)
+// Compatibility shim: previous callers without args
+pub fn ensure_c3e6_dataset_default() -> Result<PathBuf> {
+    ensure_c3e6_dataset(None)
 }
@@ -1,9 +1,39 @@
 use crate::db::{Jump, System};
+use crate::error::{Error, Result};
+use crate::spatial::SpatialIndex;
 use petgraph::graph::{Graph, NodeIndex};
 use petgraph::Undirected;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-pub type StarGraph = Graph<usize, (), Undirected>;
+/// How a ship crosses an edge of the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HopKind {
+    /// Through a stargate listed in the dataset's jumps.
+    Gate,
+    /// Straight to a system within the ship's jump range.
+    JumpDrive,
+}
+
+pub type StarGraph = Graph<usize, HopKind, Undirected>;
+
+/// The neighbours of every system by index, sorted, without duplicates or self-loops, whatever
+/// kind of hop reaches them.
+pub(crate) fn simple_neighbours(graph: &StarGraph) -> Vec<Vec<usize>> {
+    (0..graph.node_count())
+        .map(|v| {
+            let mut next: Vec<usize> = graph
+                .neighbors(NodeIndex::new(v))
+                .map(|w| w.index())
+                .filter(|&w| w != v)
+                .collect();
+            next.sort_unstable();
+            next.dedup();
+            next
+        })
+        .collect()
+}
 
 pub fn build_graph(systems: &[System], jumps: &[Jump]) -> StarGraph {
     let mut graph = StarGraph::new_undirected();
@@ -16,9 +46,44 @@ pub fn build_graph(systems: &[System], jumps: &[Jump]) -> StarGraph {
 
     for j in jumps {
         if let (Some(&a), Some(&b)) = (id_to_node.get(&j.from_id), id_to_node.get(&j.to_id)) {
-            graph.update_edge(a, b, ());
+            graph.update_edge(a, b, HopKind::Gate);
         }
     }
 
     graph
 }
+
+/// [`build_graph`] plus a [`HopKind::JumpDrive`] edge between every two systems at most
+/// `light_years` apart in a straight line that no gate already connects. Systems without
+/// coordinates keep their gates only.
+pub fn build_graph_with_jump_range(
+    systems: &[System],
+    jumps: &[Jump],
+    light_years: f64,
+) -> Result<StarGraph> {
+    if !(light_years > 0.0 && light_years.is_finite()) {
+        return Err(Error::invalid_input(format!(
+            "the jump range must be a positive number of light-years, not {}",
+            light_years
+        )));
+    }
+    let index = SpatialIndex::new(systems);
+    if index.is_empty() {
+        return Err(Error::invalid_input(
+            "the star map has no coordinates; jump-drive hops need a dataset with coordinate columns",
+        ));
+    }
+    let mut graph = build_graph(systems, jumps);
+    for (a, system) in systems.iter().enumerate() {
+        let Some(position) = system.position else {
+            continue;
+        };
+        for near in index.within(position, light_years)? {
+            let (from, to) = (NodeIndex::new(a), NodeIndex::new(near.system));
+            if a < near.system && graph.find_edge(from, to).is_none() {
+                graph.add_edge(from, to, HopKind::JumpDrive);
+            }
+        }
+    }
+    Ok(graph)
+}
@@ -0,0 +1,204 @@
+//! What a SQLite dataset contains: its tables and views with their columns and row counts, and
+//! which of the supported schema variants [`load_starmap`](crate::load_starmap) finds in it.
+
+use crate::config::SchemaMapping;
+use crate::db::{self, quote_identifier};
+use crate::error::{ErrorKind, Result, ResultExt};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// The type the column was declared with, if any; SQLite does not enforce it.
+    pub declared_type: Option<String>,
+    pub not_null: bool,
+    pub primary_key: bool,
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableInfo {
+    pub name: String,
+    /// `table` or `view`.
+    pub kind: String,
+    /// Empty when the columns cannot be read, like the row count below.
+    pub columns: Vec<ColumnInfo>,
+    /// `None` when counting failed, for example for a view over a missing table.
+    pub rows: Option<u64>,
+}
+
+/// The schema variants the loader supports, from most to least complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaVariant {
+    /// Region and constellation membership with their names, and coordinates.
+    Full,
+    /// `SolarSystems` and `Jumps` plus some, but not all, of the optional data.
+    Partial,
+    /// Only system ids and names, and jumps.
+    Minimal,
+    /// A required table or column is missing; see [`DetectedSchema::problems`].
+    Unsupported,
+}
+
+impl SchemaVariant {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SchemaVariant::Full => "full",
+            SchemaVariant::Partial => "partial",
+            SchemaVariant::Minimal => "minimal",
+            SchemaVariant::Unsupported => "unsupported",
+        }
+    }
+}
+
+/// What the loader reads from a dataset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DetectedSchema {
+    pub variant: SchemaVariant,
+    /// The systems and jumps tables, after any [`SchemaMapping`]; `None` when the mapping
+    /// itself does not fit the dataset.
+    pub systems_table: Option<String>,
+    pub jumps_table: Option<String>,
+    pub region_column: Option<String>,
+    pub constellation_column: Option<String>,
+    pub coordinate_columns: Option<[String; 3]>,
+    /// Whether region and constellation names come from `Regions` and `Constellations`.
+    pub region_names: bool,
+    pub constellation_names: bool,
+    /// Missing required tables or columns, or mapped names the dataset lacks.
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DatasetInspection {
+    pub tables: Vec<TableInfo>,
+    pub schema: DetectedSchema,
+}
+
+/// Inspect the SQLite dataset at `path`, opened read-only with [`open_dataset`](crate::open_dataset).
+pub fn inspect_dataset(path: &Path) -> Result<DatasetInspection> {
+    inspect_dataset_with(path, &SchemaMapping::default())
+}
+
+/// [`inspect_dataset`], reporting the schema as the loader sees it through `mapping`.
+pub fn inspect_dataset_with(path: &Path, mapping: &SchemaMapping) -> Result<DatasetInspection> {
+    let conn = db::open_dataset(path)?;
+    inspect(&conn, &path.display().to_string(), mapping)
+}
+
+/// [`inspect_dataset`] over a connection the caller already holds.
+pub fn inspect_connection(conn: &Connection) -> Result<DatasetInspection> {
+    inspect(conn, &db::connection_name(conn), &SchemaMapping::default())
+}
+
+fn table_info(conn: &Connection, table: &str) -> rusqlite::Result<Vec<ColumnInfo>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", quote_identifier(table)))?;
+    let columns = stmt
+        .query_map([], |row| {
+            Ok(ColumnInfo {
+                name: row.get(1)?,
+                declared_type: row.get::<_, Option<String>>(2)?.filter(|t| !t.is_empty()),
+                not_null: row.get(3)?,
+                primary_key: row.get::<_, i64>(5)? > 0,
+                default: row.get(4)?,
+            })
+        })?
+        .collect();
+    columns
+}
+
+fn inspect(conn: &Connection, db: &str, mapping: &SchemaMapping) -> Result<DatasetInspection> {
+    let unreadable = || format!("{} is not a readable SQLite database", db);
+    let mut stmt = conn
+        .prepare(
+            "SELECT name, type FROM sqlite_master
+             WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'
+             ORDER BY name",
+        )
+        .context(ErrorKind::Database, unreadable)?;
+    let objects: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()
+        .context(ErrorKind::Database, unreadable)?;
+
+    let mut tables = Vec::with_capacity(objects.len());
+    for (name, kind) in objects {
+        // A view over a dropped table cannot be described or counted; list it without either.
+        let columns = table_info(conn, &name).unwrap_or_default();
+        let rows = conn
+            .query_row(
+                &format!("SELECT count(*) FROM {}", quote_identifier(&name)),
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .ok()
+            .map(|n| n as u64);
+        tables.push(TableInfo {
+            name,
+            kind,
+            columns,
+            rows,
+        });
+    }
+
+    Ok(DatasetInspection {
+        tables,
+        schema: detect_schema(conn, db, mapping)?,
+    })
+}
+
+fn detect_schema(conn: &Connection, db: &str, mapping: &SchemaMapping) -> Result<DetectedSchema> {
+    let schema = match db::resolve_schema(conn, db, mapping) {
+        Ok(schema) => schema,
+        Err(err) if err.kind() == ErrorKind::Schema => {
+            return Ok(DetectedSchema {
+                variant: SchemaVariant::Unsupported,
+                systems_table: None,
+                jumps_table: None,
+                region_column: None,
+                constellation_column: None,
+                coordinate_columns: None,
+                region_names: false,
+                constellation_names: false,
+                problems: vec![err.to_string()],
+            })
+        }
+        Err(err) => return Err(err),
+    };
+    let problems = db::schema_problems(conn, db, &schema)?;
+    let region_names = schema.region.is_some() && schema.region_names.is_some();
+    let constellation_names =
+        schema.constellation.is_some() && schema.constellation_names.is_some();
+
+    let optional = [
+        schema.region.is_some(),
+        schema.constellation.is_some(),
+        region_names,
+        constellation_names,
+        schema.coordinates.is_some(),
+    ];
+    let variant = if !problems.is_empty() {
+        SchemaVariant::Unsupported
+    } else if optional.iter().all(|&present| present) {
+        SchemaVariant::Full
+    } else if optional.iter().any(|&present| present) {
+        SchemaVariant::Partial
+    } else {
+        SchemaVariant::Minimal
+    };
+
+    Ok(DetectedSchema {
+        variant,
+        systems_table: Some(schema.systems),
+        jumps_table: Some(schema.jumps),
+        region_column: schema.region,
+        constellation_column: schema.constellation,
+        coordinate_columns: schema.coordinates,
+        region_names,
+        constellation_names,
+        problems,
+    })
+}
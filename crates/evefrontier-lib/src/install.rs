@@ -0,0 +1,435 @@
+use crate::cache::{self, AssetMetadata};
+use crate::config::SchemaMapping;
+use crate::db;
+use crate::error::{Error, ErrorKind, Result, ResultExt};
+use crate::github::{self, DatasetSelector, DownloadOptions};
+use crate::progress::{ProgressEvent, ProgressReporter};
+use flate2::read::GzDecoder;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// The kinds of file a dataset can be delivered as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    /// A bare SQLite database.
+    Db,
+    Zip,
+    TarGz,
+    Tar,
+    /// A single gzip-compressed database.
+    Gz,
+}
+
+impl ArchiveKind {
+    const SUFFIXES: [(&'static str, ArchiveKind); 6] = [
+        (".tar.gz", ArchiveKind::TarGz),
+        (".tgz", ArchiveKind::TarGz),
+        (".tar", ArchiveKind::Tar),
+        (".zip", ArchiveKind::Zip),
+        (".db.gz", ArchiveKind::Gz),
+        (".db", ArchiveKind::Db),
+    ];
+
+    /// Detect the kind from a file name's extension.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let lower = name.to_ascii_lowercase();
+        Self::SUFFIXES
+            .iter()
+            .find(|(suffix, _)| lower.ends_with(suffix))
+            .map(|(_, kind)| *kind)
+    }
+
+    /// Detect the kind from the first bytes of a file, for sources without a useful name. A
+    /// gzip stream is a [`ArchiveKind::TarGz`] when it decompresses to a tar header and a
+    /// [`ArchiveKind::Gz`] otherwise.
+    pub fn sniff(path: &Path) -> Result<Option<Self>> {
+        let read_err = || format!("failed to read {}", path.display());
+        let file = fs::File::open(path).context(ErrorKind::Extract, || {
+            format!("failed to open {}", path.display())
+        })?;
+        let header = read_header(file).context(ErrorKind::Extract, read_err)?;
+        Ok(if header.starts_with(b"SQLite format 3\0") {
+            Some(ArchiveKind::Db)
+        } else if header.starts_with(b"PK\x03\x04") {
+            Some(ArchiveKind::Zip)
+        } else if header.starts_with(&[0x1f, 0x8b]) {
+            let inner =
+                read_header(GzDecoder::new(open(path)?)).context(ErrorKind::Extract, read_err)?;
+            Some(if is_tar(&inner) {
+                ArchiveKind::TarGz
+            } else {
+                ArchiveKind::Gz
+            })
+        } else if is_tar(&header) {
+            Some(ArchiveKind::Tar)
+        } else {
+            None
+        })
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ArchiveKind::Db => ".db",
+            ArchiveKind::Zip => ".zip",
+            ArchiveKind::TarGz => ".tar.gz",
+            ArchiveKind::Tar => ".tar",
+            ArchiveKind::Gz => ".db.gz",
+        }
+    }
+}
+
+/// The first bytes of `reader`, enough to hold a tar header's magic.
+fn read_header(reader: impl Read) -> std::io::Result<Vec<u8>> {
+    let mut header = Vec::with_capacity(262);
+    reader.take(262).read_to_end(&mut header)?;
+    Ok(header)
+}
+
+fn is_tar(header: &[u8]) -> bool {
+    header.len() >= 262 && &header[257..262] == b"ustar"
+}
+
+/// Name of the directory an archive named `name` is extracted into, or `None` for a bare DB.
+pub(crate) fn archive_stem(name: &str) -> Option<&str> {
+    let kind = ArchiveKind::from_name(name)?;
+    if kind == ArchiveKind::Db {
+        return None;
+    }
+    let suffix = ArchiveKind::SUFFIXES
+        .iter()
+        .find(|(suffix, _)| name.to_ascii_lowercase().ends_with(suffix))
+        .map(|(suffix, _)| suffix.len())?;
+    Some(&name[..name.len() - suffix])
+}
+
+/// Produce the database contained in a cached `asset`, extracting archives into the asset's
+/// extraction directory. Previously extracted databases are reused.
+pub(crate) fn extract_db(
+    asset: &Path,
+    selector: &DatasetSelector,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf> {
+    let name = asset
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| Error::extract(format!("Invalid asset path {}", asset.display())))?;
+    let kind = ArchiveKind::from_name(name).ok_or_else(|| unsupported(name))?;
+    if kind == ArchiveKind::Db {
+        return Ok(asset.to_path_buf());
+    }
+
+    // Each asset gets its own extraction directory so entries with the same name in
+    // different assets do not shadow each other.
+    let out_dir = cache::extraction_dir(asset).ok_or_else(|| unsupported(name))?;
+    fs::create_dir_all(&out_dir).context(ErrorKind::Extract, || {
+        format!(
+            "failed to create extraction directory {}",
+            out_dir.display()
+        )
+    })?;
+
+    match kind {
+        ArchiveKind::Zip => extract_from_zip(asset, &out_dir, selector, progress),
+        ArchiveKind::Tar => {
+            let file = open(asset)?;
+            extract_from_tar(tar::Archive::new(file), asset, &out_dir, selector, progress)
+        }
+        ArchiveKind::TarGz => {
+            let file = open(asset)?;
+            let archive = tar::Archive::new(GzDecoder::new(file));
+            extract_from_tar(archive, asset, &out_dir, selector, progress)
+        }
+        ArchiveKind::Gz => {
+            let entry = archive_stem(name).unwrap_or("dataset");
+            let db_name = format!("{}.db", entry.trim_end_matches(".db"));
+            let out_path = out_dir.join(&db_name);
+            if out_path.exists() {
+                progress.report(&ProgressEvent::UsingCached { path: &out_path });
+                return Ok(out_path);
+            }
+            let mut reader = GzDecoder::new(open(asset)?);
+            write_entry(&db_name, &mut reader, &out_path, progress)?;
+            Ok(out_path)
+        }
+        ArchiveKind::Db => unreachable!("handled above"),
+    }
+}
+
+fn unsupported(name: &str) -> Error {
+    Error::extract(format!("Unsupported asset type: {}", name))
+}
+
+fn open(path: &Path) -> Result<fs::File> {
+    fs::File::open(path).context(ErrorKind::Extract, || {
+        format!("failed to open archive {}", path.display())
+    })
+}
+
+/// Stream `reader` to `out_path` through a temporary file so an interrupted extraction never
+/// leaves a truncated DB that later runs would mistake for a cached one.
+fn write_entry(
+    entry: &str,
+    reader: &mut dyn Read,
+    out_path: &Path,
+    progress: &dyn ProgressReporter,
+) -> Result<()> {
+    progress.report(&ProgressEvent::ExtractStarted {
+        entry,
+        dest: out_path,
+    });
+    let tmp = out_path.with_extension("db.tmp");
+    let mut out = fs::File::create(&tmp).context(ErrorKind::Extract, || {
+        format!("failed to create {}", tmp.display())
+    })?;
+    std::io::copy(reader, &mut out).context(ErrorKind::Extract, || {
+        format!("failed to extract {}", entry)
+    })?;
+    fs::rename(&tmp, out_path).context(ErrorKind::Extract, || {
+        format!("failed to move extracted DB to {}", out_path.display())
+    })?;
+    progress.report(&ProgressEvent::ExtractFinished { dest: out_path });
+    Ok(())
+}
+
+fn extract_from_zip(
+    zip_path: &Path,
+    out_dir: &Path,
+    selector: &DatasetSelector,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf> {
+    let file = fs::File::open(zip_path).context(ErrorKind::Extract, || {
+        format!("failed to open zip file {}", zip_path.display())
+    })?;
+    let mut archive = ZipArchive::new(file).context(ErrorKind::Extract, || {
+        format!("failed to read zip archive {}", zip_path.display())
+    })?;
+
+    let mut entries = Vec::with_capacity(archive.len());
+    let read_err = || format!("failed to read zip archive {}", zip_path.display());
+    for i in 0..archive.len() {
+        let entry = archive
+            .by_index_raw(i)
+            .context(ErrorKind::Extract, read_err)?;
+        entries.push(entry.name().to_string());
+    }
+    let idx = selector.select_entry(&entries)?;
+    let mut db_file = archive
+        .by_index(idx)
+        .context(ErrorKind::Extract, read_err)?;
+    let entry = db_file.name().to_string();
+    let db_name = entry.rsplit('/').next().unwrap_or("c3e6.db");
+    let out_path = out_dir.join(db_name);
+
+    if out_path.exists() {
+        progress.report(&ProgressEvent::UsingCached { path: &out_path });
+        return Ok(out_path);
+    }
+
+    write_entry(&entry, &mut db_file, &out_path, progress)?;
+    Ok(out_path)
+}
+
+fn extract_from_tar<R: Read>(
+    mut archive: tar::Archive<R>,
+    tar_path: &Path,
+    out_dir: &Path,
+    selector: &DatasetSelector,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf> {
+    // Tar entries can only be read in order, so list the names in a first pass.
+    let entries: Vec<String> = archive
+        .entries()
+        .context(ErrorKind::Extract, || {
+            format!("failed to read tar archive {}", tar_path.display())
+        })?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.header().entry_type().is_file())
+        .filter_map(|e| e.path().ok().map(|p| p.to_string_lossy().into_owned()))
+        .collect();
+    let wanted = entries[selector.select_entry(&entries)?].clone();
+    let db_name = wanted.rsplit('/').next().unwrap_or("c3e6.db").to_string();
+    let out_path = out_dir.join(&db_name);
+
+    if out_path.exists() {
+        progress.report(&ProgressEvent::UsingCached { path: &out_path });
+        return Ok(out_path);
+    }
+
+    // Reopen for the second pass; tar archives are not seekable through a decoder.
+    let file = open(tar_path)?;
+    let reader: Box<dyn Read> = match ArchiveKind::from_name(&tar_path.to_string_lossy()) {
+        Some(ArchiveKind::TarGz) => Box::new(GzDecoder::new(file)),
+        _ => Box::new(file),
+    };
+    let mut archive = tar::Archive::new(reader);
+    let read_err = || format!("failed to read tar archive {}", tar_path.display());
+    for entry in archive.entries().context(ErrorKind::Extract, read_err)? {
+        let mut entry = entry.context(ErrorKind::Extract, read_err)?;
+        let path = entry
+            .path()
+            .context(ErrorKind::Extract, read_err)?
+            .to_string_lossy()
+            .into_owned();
+        if path == wanted {
+            write_entry(&wanted, &mut entry, &out_path, progress)?;
+            return Ok(out_path);
+        }
+    }
+    Err(Error::extract(format!(
+        "entry {} disappeared from {}",
+        wanted,
+        tar_path.display()
+    )))
+}
+
+/// Validate `db_path` through `mapping` and atomically copy it to `target`, recording the
+/// provenance of `asset`, listed in the cache as `asset_name`, in a `<target>.meta.json`
+/// sidecar.
+pub(crate) fn install_db(
+    db_path: &Path,
+    asset: &Path,
+    asset_name: &str,
+    target: &Path,
+    mapping: &SchemaMapping,
+) -> Result<PathBuf> {
+    db::validate_dataset_with(db_path, mapping)?;
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).context(ErrorKind::Io, || {
+            format!("Failed to create data directory {}", parent.display())
+        })?;
+    }
+
+    // copy to temp and rename
+    let tmp = target.with_extension("db.tmp");
+    fs::copy(db_path, &tmp).context(ErrorKind::Io, || {
+        format!("failed to copy DB to {}", tmp.display())
+    })?;
+    fs::rename(&tmp, target).context(ErrorKind::Io, || {
+        format!("failed to move DB to {}", target.display())
+    })?;
+
+    let asset_meta = cache::read_metadata(asset)?.unwrap_or_default();
+    let provenance = AssetMetadata {
+        asset: Some(asset_name.to_string()),
+        entry: if db_path == asset {
+            None
+        } else {
+            db_path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+        },
+        sha256: Some(cache::sha256_file(target)?),
+        size: Some(
+            fs::metadata(target)
+                .context(ErrorKind::Io, || {
+                    format!("failed to stat {}", target.display())
+                })?
+                .len(),
+        ),
+        ..asset_meta
+    };
+    cache::write_metadata(target, &provenance)?;
+    Ok(target.to_path_buf())
+}
+
+/// Install a dataset from a local file or an `http(s)://` URL without consulting the GitHub
+/// API. The source may be a `.db`, a `.zip`, a `.tar`, `.tar.gz`/`.tgz` or a `.db.gz`; it is
+/// copied into the `local/` directory of `options.cache_root`, extracted, validated with
+/// `options.schema` and atomically installed at `target`, replacing any DB already there.
+/// `options.release_api` is not used.
+pub fn install_from(
+    source: &str,
+    target: &Path,
+    selector: &DatasetSelector,
+    options: &DownloadOptions,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf> {
+    let cache_root = options.cache_root()?;
+    let _lock = cache::lock_cache(&cache_root)?;
+    // Local assets get their own directory so they never replace a release asset.
+    let local = cache_root.join(cache::LOCAL_DIR);
+    fs::create_dir_all(&local).context(ErrorKind::Io, || {
+        format!("failed to create cache directory {}", local.display())
+    })?;
+
+    let is_url = source.starts_with("http://") || source.starts_with("https://");
+    let file_name = if is_url {
+        let path = source.split(['?', '#']).next().unwrap_or(source);
+        path.rsplit('/').next().unwrap_or_default().to_string()
+    } else {
+        Path::new(source)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| Error::invalid_input(format!("Invalid source path {}", source)))?
+            .to_string()
+    };
+
+    let staged = local.join(format!("{}.download", sanitize(&file_name)));
+    let provenance = if is_url {
+        github::download_to(source, &staged, progress)?;
+        source.to_string()
+    } else {
+        let path = Path::new(source);
+        fs::copy(path, &staged).context(ErrorKind::Io, || {
+            format!("failed to copy {} into the cache", path.display())
+        })?;
+        path.canonicalize()
+            .unwrap_or_else(|_| path.to_path_buf())
+            .display()
+            .to_string()
+    };
+
+    // Name the cached asset so its kind is recognisable, sniffing when the name does not say.
+    let kind = match ArchiveKind::from_name(&file_name) {
+        Some(kind) => kind,
+        None => ArchiveKind::sniff(&staged)?.ok_or_else(|| {
+            let _ = fs::remove_file(&staged);
+            Error::extract(format!(
+                "Cannot tell what {} is; expected a .db, .zip, .tar, .tar.gz or .db.gz",
+                source
+            ))
+        })?,
+    };
+    let asset_name = if ArchiveKind::from_name(&file_name).is_some() {
+        sanitize(&file_name)
+    } else {
+        format!("{}{}", sanitize(&file_name), kind.extension())
+    };
+    let asset = local.join(&asset_name);
+
+    // A fresh copy replaces any earlier asset of the same name, including its extraction.
+    if let Some(dir) = cache::extraction_dir(&asset).filter(|d| d.is_dir()) {
+        fs::remove_dir_all(&dir).context(ErrorKind::Io, || {
+            format!("failed to remove {}", dir.display())
+        })?;
+    }
+    fs::rename(&staged, &asset).context(ErrorKind::Io, || {
+        format!("failed to move download to {}", asset.display())
+    })?;
+    cache::record_download(&asset, None, &provenance)?;
+
+    let db_path = extract_db(&asset, selector, progress)?;
+    let cache_name = format!("{}/{}", cache::LOCAL_DIR, asset_name);
+    install_db(&db_path, &asset, &cache_name, target, &options.schema)
+}
+
+fn sanitize(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if cleaned.trim_matches('.').is_empty() {
+        "dataset".to_string()
+    } else {
+        cleaned
+    }
+}
@@ -1,9 +1,79 @@
+pub mod alternatives;
+#[cfg(feature = "download")]
+pub mod cache;
+pub mod centrality;
+pub mod chokepoints;
+pub mod components;
+pub mod config;
 pub mod db;
+pub mod diff;
+pub mod error;
+pub mod export;
+#[cfg(feature = "download")]
 pub mod github;
 pub mod graph;
+pub mod inspect;
+#[cfg(feature = "download")]
+pub mod install;
+pub mod nearby;
+#[cfg(feature = "async")]
+pub mod nonblocking;
 pub mod path;
+pub mod progress;
+pub mod refuel;
+pub mod ship;
+pub mod snapshot;
+pub mod source;
+pub mod spatial;
+pub mod starmap;
+pub mod stats;
+#[cfg(feature = "synthetic")]
+pub mod synthetic;
 
-pub use db::{load_starmap, Jump, System};
-pub use github::{ensure_c3e6_dataset, ensure_c3e6_dataset_default};
-pub use graph::build_graph;
-pub use path::optimal_route;
+pub use alternatives::{
+    alternative_routes, k_shortest_paths, Alternative, Alternatives, AlternativesConfig, Shortfall,
+};
+#[cfg(feature = "download")]
+pub use cache::{
+    cache_entry, clear_cache, default_cache_root, installed_asset, list_cache, prune_cache,
+    AssetMetadata, CacheEntry, PrunePolicy,
+};
+pub use centrality::{compute_centrality, Centrality, CentralityConfig, CentralityMetric};
+pub use chokepoints::{find_chokepoints, Chokepoints};
+pub use components::{connected_components, unreachable_from, UnreachableRegion};
+pub use config::{Config, SchemaMapping};
+pub use db::{
+    find_system, load_starmap, load_starmap_from_connection, load_starmap_from_connection_with,
+    load_starmap_with, open_dataset, validate_dataset, validate_dataset_with, Jump, Position,
+    System, LIGHT_YEAR,
+};
+pub use diff::{diff_starmaps, StarmapDiff};
+pub use error::{Error, ErrorKind, Result};
+pub use export::{export_starmap, ExportFormat};
+#[cfg(feature = "download")]
+pub use github::{
+    ensure_c3e6_dataset, ensure_c3e6_dataset_default, ensure_dataset, ensure_dataset_with,
+    latest_release, DatasetSelector, DownloadOptions, NamePattern, ReleaseAsset, ReleaseInfo,
+};
+pub use graph::{build_graph, build_graph_with_jump_range, HopKind, StarGraph};
+pub use inspect::{inspect_dataset, inspect_dataset_with, DatasetInspection, SchemaVariant};
+#[cfg(feature = "download")]
+pub use install::{install_from, ArchiveKind};
+pub use nearby::{nearby, Nearby, Reach};
+pub use path::{
+    optimal_route, optimal_route_with_progress, route_between, route_hops, shortest_path, Hop,
+};
+pub use progress::{LogProgress, ProgressEvent, ProgressReporter, SilentProgress};
+pub use refuel::{load_refuel_points, refuel_route, RefuelRoute, RefuelStop};
+/// The `rusqlite` version [`load_starmap_from_connection`] expects connections from.
+pub use rusqlite;
+pub use ship::{FuelUse, ShipProfile};
+pub use snapshot::SNAPSHOT_VERSION;
+pub use source::{
+    open_source, open_source_with, CsvSource, JsonSource, SourceFormat, SqliteSource, StarmapSource,
+};
+pub use spatial::{Neighbour, SpatialIndex};
+pub use starmap::Starmap;
+pub use stats::{graph_stats, GraphStats};
+#[cfg(feature = "synthetic")]
+pub use synthetic::{generate, GalaxyConfig, SyntheticSchema};
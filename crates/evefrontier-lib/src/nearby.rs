@@ -0,0 +1,210 @@
+//! Systems near a starting system, grouped into rings by distance: every system within N gate
+//! jumps, or within D light-years of travel through gates when the dataset has coordinates.
+
+use crate::db::LIGHT_YEAR;
+use crate::error::{Error, Result};
+use crate::starmap::Starmap;
+use petgraph::graph::NodeIndex;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// How far from the start to look, and how wide each ring is.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "unit", rename_all = "snake_case")]
+pub enum Reach {
+    /// Systems at most `max` jumps away; ring `k` holds the systems exactly `k` jumps away.
+    Jumps { max: usize },
+    /// Systems at most `max` light-years of gate travel away, summing the straight-line length
+    /// of each gate. Ring `k` holds the systems more than `(k - 1) * ring_width` and at most
+    /// `k * ring_width` light-years away; ring 0 holds the start.
+    LightYears { max: f64, ring_width: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NearbySystem {
+    pub system: usize,
+    /// Jumps on the route found to the system: the fewest possible for [`Reach::Jumps`], and
+    /// those of the shortest route in light-years for [`Reach::LightYears`].
+    pub jumps: usize,
+    /// Light-years of gate travel, for [`Reach::LightYears`] only.
+    pub light_years: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Ring {
+    pub index: usize,
+    /// Nearest first, then in index order.
+    pub systems: Vec<NearbySystem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Nearby {
+    pub start: usize,
+    pub reach: Reach,
+    /// Rings in ascending order, starting with ring 0 for the start; empty rings are left out.
+    pub rings: Vec<Ring>,
+}
+
+impl Nearby {
+    /// Number of systems found, not counting the start.
+    pub fn count(&self) -> usize {
+        self.rings.iter().map(|r| r.systems.len()).sum::<usize>() - 1
+    }
+}
+
+/// Every system within `reach` of `start`, grouped into rings.
+///
+/// For [`Reach::LightYears`] the start must have coordinates; systems without them cannot be
+/// measured and are never reached.
+pub fn nearby(starmap: &Starmap, start: usize, reach: Reach) -> Result<Nearby> {
+    let found = match reach {
+        Reach::Jumps { max } => within_jumps(starmap, start, max),
+        Reach::LightYears { max, ring_width } => {
+            if !(max >= 0.0 && max.is_finite()) {
+                return Err(Error::invalid_input(format!(
+                    "the light-year range must be a non-negative number, not {}",
+                    max
+                )));
+            }
+            if !(ring_width > 0.0 && ring_width.is_finite()) {
+                return Err(Error::invalid_input(format!(
+                    "the ring width must be a positive number of light-years, not {}",
+                    ring_width
+                )));
+            }
+            within_light_years(starmap, start, max)?
+        }
+    };
+
+    let mut rings: Vec<Ring> = Vec::new();
+    for system in found {
+        let index = match (reach, system.light_years) {
+            (Reach::LightYears { ring_width, .. }, Some(ly)) => (ly / ring_width).ceil() as usize,
+            _ => system.jumps,
+        };
+        match rings.last_mut() {
+            Some(ring) if ring.index == index => ring.systems.push(system),
+            _ => rings.push(Ring {
+                index,
+                systems: vec![system],
+            }),
+        }
+    }
+    Ok(Nearby {
+        start,
+        reach,
+        rings,
+    })
+}
+
+/// Breadth-first, so systems come out nearest first and in index order within each jump count.
+fn within_jumps(starmap: &Starmap, start: usize, max: usize) -> Vec<NearbySystem> {
+    let graph = &starmap.graph;
+    let mut jumps = vec![usize::MAX; graph.node_count()];
+    let mut queue = VecDeque::from([start]);
+    jumps[start] = 0;
+    let mut found = Vec::new();
+    while let Some(v) = queue.pop_front() {
+        found.push(NearbySystem {
+            system: v,
+            jumps: jumps[v],
+            light_years: None,
+        });
+        if jumps[v] == max {
+            continue;
+        }
+        for w in graph.neighbors(NodeIndex::new(v)) {
+            let w = w.index();
+            if jumps[w] == usize::MAX {
+                jumps[w] = jumps[v] + 1;
+                queue.push_back(w);
+            }
+        }
+    }
+    found.sort_by_key(|s| (s.jumps, s.system));
+    found
+}
+
+/// A system waiting in Dijkstra's queue, ordered so the nearest is popped first.
+struct Queued {
+    light_years: f64,
+    system: usize,
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .light_years
+            .total_cmp(&self.light_years)
+            .then(other.system.cmp(&self.system))
+    }
+}
+
+fn within_light_years(starmap: &Starmap, start: usize, max: f64) -> Result<Vec<NearbySystem>> {
+    let Starmap { systems, graph, .. } = starmap;
+    if systems[start].position.is_none() {
+        return Err(Error::invalid_input(format!(
+            "{} has no coordinates; light-year distances need a dataset with coordinate columns",
+            systems[start].name
+        )));
+    }
+    let n = graph.node_count();
+    let mut best = vec![f64::INFINITY; n];
+    let mut jumps = vec![0; n];
+    let mut done = vec![false; n];
+    let mut heap = BinaryHeap::from([Queued {
+        light_years: 0.0,
+        system: start,
+    }]);
+    best[start] = 0.0;
+    let mut found = Vec::new();
+    while let Some(Queued {
+        light_years: here,
+        system: v,
+    }) = heap.pop()
+    {
+        if done[v] {
+            continue;
+        }
+        done[v] = true;
+        found.push(NearbySystem {
+            system: v,
+            jumps: jumps[v],
+            light_years: Some(here),
+        });
+        let Some(from) = systems[v].position else {
+            continue;
+        };
+        for w in graph.neighbors(NodeIndex::new(v)) {
+            let w = w.index();
+            let Some(to) = systems[w].position else {
+                continue;
+            };
+            let there = here + from.distance(to) / LIGHT_YEAR;
+            if there <= max && there < best[w] {
+                best[w] = there;
+                jumps[w] = jumps[v] + 1;
+                heap.push(Queued {
+                    light_years: there,
+                    system: w,
+                });
+            }
+        }
+    }
+    Ok(found)
+}
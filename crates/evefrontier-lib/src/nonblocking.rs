@@ -0,0 +1,205 @@
+//! Async variants of the dataset downloader, for callers running inside a tokio runtime.
+//!
+//! The blocking entry points in [`crate::github`] build a `reqwest::blocking::Client`, which
+//! panics when used from async code. These functions fetch over the async client and run the
+//! filesystem work (cache locking, extraction, validation and installation) on tokio's
+//! blocking pool, sharing that code with the blocking path.
+
+use crate::cache;
+use crate::error::{ErrorKind, Result, ResultExt};
+use crate::github::{self, DatasetSelector, DownloadOptions, Release, ReleaseInfo, USER_AGENT};
+use crate::progress::{ProgressEvent, ProgressReporter, SilentProgress};
+use reqwest::Client;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+
+/// Run filesystem-bound work off the async executor.
+async fn blocking<T, F>(work: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .context(ErrorKind::Io, || "blocking task failed")?
+}
+
+async fn fetch_latest_release(client: &Client, api_url: &str) -> Result<ReleaseInfo> {
+    let release: Release = client
+        .get(api_url)
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .await
+        .context(ErrorKind::Download, || {
+            "failed to query GitHub releases API"
+        })?
+        .error_for_status()
+        .context(ErrorKind::Download, || "GitHub releases API returned error")?
+        .json()
+        .await
+        .context(ErrorKind::Download, || {
+            "failed to parse GitHub releases JSON"
+        })?;
+    Ok(release.into())
+}
+
+async fn download_asset(
+    client: &Client,
+    url: &str,
+    dest: &Path,
+    progress: &dyn ProgressReporter,
+) -> Result<()> {
+    let mut resp = client
+        .get(url)
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .await
+        .context(ErrorKind::Download, || "failed to start download")?
+        .error_for_status()
+        .context(ErrorKind::Download, || "download request returned error")?;
+
+    let total_bytes = resp.content_length();
+    progress.report(&ProgressEvent::DownloadStarted { url, total_bytes });
+
+    let tmp = github::part_path(dest);
+    let mut file = tokio::fs::File::create(&tmp)
+        .await
+        .context(ErrorKind::Download, || {
+            format!("failed to create file {}", tmp.display())
+        })?;
+    let mut downloaded: u64 = 0;
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .context(ErrorKind::Download, || "download read error")?
+    {
+        file.write_all(&chunk)
+            .await
+            .context(ErrorKind::Download, || {
+                format!("failed to write {}", tmp.display())
+            })?;
+        downloaded += chunk.len() as u64;
+        progress.report(&ProgressEvent::DownloadProgress {
+            downloaded,
+            total_bytes,
+        });
+    }
+    file.flush().await.context(ErrorKind::Download, || {
+        format!("failed to write {}", tmp.display())
+    })?;
+    drop(file);
+    tokio::fs::rename(&tmp, dest)
+        .await
+        .context(ErrorKind::Download, || {
+            format!("failed to move download to {}", dest.display())
+        })?;
+    progress.report(&ProgressEvent::DownloadFinished { path: dest });
+    Ok(())
+}
+
+/// Query the GitHub releases API for the latest dataset release and its assets.
+pub async fn latest_release() -> Result<ReleaseInfo> {
+    fetch_latest_release(&Client::new(), &DownloadOptions::default().release_api).await
+}
+
+/// Async counterpart of [`crate::ensure_c3e6_dataset`].
+pub async fn ensure_c3e6_dataset(target_db: Option<&Path>) -> Result<PathBuf> {
+    ensure_dataset(
+        target_db,
+        &DatasetSelector::default(),
+        Arc::new(SilentProgress),
+    )
+    .await
+}
+
+/// Async counterpart of [`crate::ensure_dataset`].
+///
+/// `progress` is shared with the blocking pool that runs extraction, hence the `Arc`.
+pub async fn ensure_dataset(
+    target_db: Option<&Path>,
+    selector: &DatasetSelector,
+    progress: Arc<dyn ProgressReporter>,
+) -> Result<PathBuf> {
+    ensure_dataset_with(target_db, selector, &DownloadOptions::default(), progress).await
+}
+
+/// Async counterpart of [`crate::ensure_dataset_with`]. It takes the same cache lock, so the
+/// blocking and async downloaders can share a cache directory.
+pub async fn ensure_dataset_with(
+    target_db: Option<&Path>,
+    selector: &DatasetSelector,
+    options: &DownloadOptions,
+    progress: Arc<dyn ProgressReporter>,
+) -> Result<PathBuf> {
+    let client = Client::new();
+    let release = fetch_latest_release(&client, &options.release_api).await?;
+    let asset = selector.select_asset(&release.assets)?.clone();
+
+    let target = target_db.map(Path::to_path_buf);
+    let existing = {
+        let (target, progress) = (target.clone(), progress.clone());
+        let (name, selector, options) = (asset.name.clone(), selector.clone(), options.clone());
+        blocking(move || {
+            github::existing_target(
+                target.as_deref(),
+                &name,
+                &selector,
+                &options,
+                progress.as_ref(),
+            )
+        })
+        .await?
+    };
+    if let Some(existing) = existing {
+        return Ok(existing);
+    }
+
+    let cache_root = options.cache_root()?;
+    let lock = {
+        let root = cache_root.clone();
+        blocking(move || cache::lock_cache(&root)).await?
+    };
+    let cached_asset_path = cache_root.join(&asset.name);
+    if options.force {
+        let path = cached_asset_path.clone();
+        blocking(move || cache::remove_asset(&path)).await?;
+    }
+    let already_cached = tokio::fs::try_exists(&cached_asset_path)
+        .await
+        .context(ErrorKind::Io, || {
+            format!("failed to stat {}", cached_asset_path.display())
+        })?;
+    if !already_cached {
+        download_asset(
+            &client,
+            &asset.download_url,
+            &cached_asset_path,
+            progress.as_ref(),
+        )
+        .await?;
+    } else {
+        progress.report(&ProgressEvent::UsingCached {
+            path: &cached_asset_path,
+        });
+    }
+
+    let selector = selector.clone();
+    let mapping = options.schema.clone();
+    blocking(move || {
+        let _lock = lock;
+        if already_cached {
+            cache::touch(&cached_asset_path)?;
+        } else {
+            cache::record_download(&cached_asset_path, Some(&release.tag), &asset.download_url)?;
+        }
+        github::finish_dataset(
+            &cached_asset_path,
+            target.as_deref(),
+            &selector,
+            &mapping,
+            progress.as_ref(),
+        )
+    })
+    .await
+}
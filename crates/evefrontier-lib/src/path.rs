@@ -1,8 +1,25 @@
-use crate::graph::StarGraph;
+use crate::db::{find_system, System, LIGHT_YEAR};
+use crate::error::{Error, Result};
+use crate::graph::{HopKind, StarGraph};
+use crate::progress::{ProgressEvent, ProgressReporter, SilentProgress};
 use petgraph::algo::astar;
 use petgraph::graph::NodeIndex;
+use serde::Serialize;
+use std::collections::VecDeque;
 
+/// A tour from `start_idx` that repeatedly goes to the nearest unvisited system until every
+/// system it can reach is visited, then returns to `start_idx`. Each leg is one breadth-first
+/// search, so a tour grows with the square of the component size.
 pub fn optimal_route(graph: &StarGraph, start_idx: usize) -> Vec<usize> {
+    optimal_route_with_progress(graph, start_idx, &SilentProgress)
+}
+
+/// [`optimal_route`], reporting how many systems have been visited after each leg.
+pub fn optimal_route_with_progress(
+    graph: &StarGraph,
+    start_idx: usize,
+    progress: &dyn ProgressReporter,
+) -> Vec<usize> {
     let node_count = graph.node_count();
     let mut visited = vec![false; node_count];
     let mut route: Vec<usize> = Vec::new();
@@ -11,39 +28,21 @@ pub fn optimal_route(graph: &StarGraph, start_idx: usize) -> Vec<usize> {
     let mut current = start_node;
     route.push(current.index());
     visited[current.index()] = true;
+    let mut visited_count = 1;
 
-    loop {
-        let mut best_path: Option<(f32, Vec<NodeIndex>)> = None;
-
-        for target in graph.node_indices() {
-            if visited[target.index()] {
-                continue;
-            }
-
-            if let Some((cost, path)) =
-                astar(graph, current, |n| n == target, |_| 1.0_f32, |_| 0.0_f32)
-            {
-                match &best_path {
-                    None => best_path = Some((cost, path)),
-                    Some((best_cost, _)) if cost < *best_cost => {
-                        best_path = Some((cost, path));
-                    }
-                    _ => {}
-                }
+    while let Some(path) = nearest_unvisited(graph, current, &visited) {
+        for &idx in path.iter().skip(1) {
+            route.push(idx);
+            if !visited[idx] {
+                visited[idx] = true;
+                visited_count += 1;
             }
         }
-
-        match best_path {
-            Some((_cost, path)) => {
-                for node in path.iter().skip(1) {
-                    let idx = node.index();
-                    route.push(idx);
-                    visited[idx] = true;
-                }
-                current = *path.last().unwrap();
-            }
-            None => break,
-        }
+        current = NodeIndex::new(*path.last().unwrap());
+        progress.report(&ProgressEvent::RouteProgress {
+            visited: visited_count,
+            total: node_count,
+        });
     }
 
     if current != start_node {
@@ -60,5 +59,105 @@ pub fn optimal_route(graph: &StarGraph, start_idx: usize) -> Vec<usize> {
         }
     }
 
+    progress.report(&ProgressEvent::RouteFinished {
+        length: route.len(),
+    });
     route
 }
+
+/// Path from `from` to the unvisited system the fewest jumps away, the lowest-indexed one among
+/// equals, or `None` when every reachable system has been visited. A single breadth-first
+/// search that stops after the first layer with an unvisited system, so a whole tour costs one
+/// search per leg rather than one per leg and candidate.
+fn nearest_unvisited(graph: &StarGraph, from: NodeIndex, visited: &[bool]) -> Option<Vec<usize>> {
+    let mut parent = vec![usize::MAX; graph.node_count()];
+    let mut jumps = vec![0usize; graph.node_count()];
+    parent[from.index()] = from.index();
+    let mut queue = VecDeque::from([from]);
+    let mut best: Option<usize> = None;
+    while let Some(v) = queue.pop_front() {
+        if best.is_some_and(|b| jumps[v.index()] >= jumps[b]) {
+            break;
+        }
+        for w in graph.neighbors(v) {
+            let w = w.index();
+            if parent[w] != usize::MAX {
+                continue;
+            }
+            parent[w] = v.index();
+            jumps[w] = jumps[v.index()] + 1;
+            if !visited[w] && best.is_none_or(|b| w < b) {
+                best = Some(w);
+            }
+            queue.push_back(NodeIndex::new(w));
+        }
+    }
+
+    let mut path = vec![best?];
+    while path[path.len() - 1] != from.index() {
+        path.push(parent[path[path.len() - 1]]);
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Fewest-jumps path from `start_idx` to `goal_idx`, or `None` when they are not connected.
+pub fn shortest_path(graph: &StarGraph, start_idx: usize, goal_idx: usize) -> Option<Vec<usize>> {
+    let goal = NodeIndex::new(goal_idx);
+    astar(
+        graph,
+        NodeIndex::new(start_idx),
+        |n| n == goal,
+        |_| 1.0_f32,
+        |_| 0.0_f32,
+    )
+    .map(|(_cost, path)| path.into_iter().map(|n| n.index()).collect())
+}
+
+/// Fewest-jumps route between two systems given by name.
+pub fn route_between(
+    systems: &[System],
+    graph: &StarGraph,
+    from: &str,
+    to: &str,
+) -> Result<Vec<usize>> {
+    let start_idx = find_system(systems, from)?;
+    let goal_idx = find_system(systems, to)?;
+    shortest_path(graph, start_idx, goal_idx).ok_or_else(|| Error::NoRoute {
+        from: from.to_string(),
+        to: to.to_string(),
+    })
+}
+
+/// One step of a route.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Hop {
+    pub from: usize,
+    pub to: usize,
+    pub kind: HopKind,
+    /// Straight-line length of the hop, when both systems have coordinates.
+    pub light_years: Option<f64>,
+}
+
+/// The hops between consecutive systems of `route`, which must be a path in `graph`.
+pub fn route_hops(systems: &[System], graph: &StarGraph, route: &[usize]) -> Vec<Hop> {
+    route
+        .windows(2)
+        .map(|pair| {
+            let (from, to) = (pair[0], pair[1]);
+            let edge = graph
+                .find_edge(NodeIndex::new(from), NodeIndex::new(to))
+                .expect("consecutive systems of a route are connected");
+            let light_years = match (systems[from].position, systems[to].position) {
+                (Some(a), Some(b)) => Some(a.distance(b) / LIGHT_YEAR),
+                _ => None,
+            };
+            Hop {
+                from,
+                to,
+                kind: graph[edge],
+                light_years,
+            }
+        })
+        .collect()
+}
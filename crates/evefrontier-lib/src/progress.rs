@@ -0,0 +1,113 @@
+use std::path::Path;
+
+/// Something long-running the library is doing, reported through a [`ProgressReporter`].
+#[derive(Debug, Clone)]
+pub enum ProgressEvent<'a> {
+    /// A download began; `total_bytes` is `None` when the server sent no length.
+    DownloadStarted {
+        url: &'a str,
+        total_bytes: Option<u64>,
+    },
+    DownloadProgress {
+        downloaded: u64,
+        total_bytes: Option<u64>,
+    },
+    DownloadFinished {
+        path: &'a Path,
+    },
+    /// An existing DB, cached asset or previously extracted DB is reused.
+    UsingCached {
+        path: &'a Path,
+    },
+    ExtractStarted {
+        entry: &'a str,
+        dest: &'a Path,
+    },
+    ExtractFinished {
+        dest: &'a Path,
+    },
+    /// `visited` of the `total` systems in the graph are on the route so far.
+    RouteProgress {
+        visited: usize,
+        total: usize,
+    },
+    RouteFinished {
+        length: usize,
+    },
+}
+
+/// Receives [`ProgressEvent`]s. Implementations must be cheap: download progress is reported
+/// for every chunk read.
+pub trait ProgressReporter: Send + Sync {
+    fn report(&self, event: &ProgressEvent<'_>);
+}
+
+/// Discards every event. Used by the plain library entry points.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SilentProgress;
+
+impl ProgressReporter for SilentProgress {
+    fn report(&self, _event: &ProgressEvent<'_>) {}
+}
+
+/// Emits events as `key=value` records through the `log` facade under the
+/// `evefrontier::progress` target. Milestones are logged at `info`, per-chunk and per-step
+/// updates at `trace`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogProgress;
+
+const LOG_TARGET: &str = "evefrontier::progress";
+
+impl ProgressReporter for LogProgress {
+    fn report(&self, event: &ProgressEvent<'_>) {
+        match event {
+            ProgressEvent::DownloadStarted { url, total_bytes } => log::info!(
+                target: LOG_TARGET,
+                "event=download_started url={} total_bytes={}",
+                url,
+                total_bytes.map_or_else(|| "unknown".to_string(), |t| t.to_string())
+            ),
+            ProgressEvent::DownloadProgress {
+                downloaded,
+                total_bytes,
+            } => log::trace!(
+                target: LOG_TARGET,
+                "event=download_progress downloaded={} total_bytes={}",
+                downloaded,
+                total_bytes.map_or_else(|| "unknown".to_string(), |t| t.to_string())
+            ),
+            ProgressEvent::DownloadFinished { path } => log::info!(
+                target: LOG_TARGET,
+                "event=download_finished path={}",
+                path.display()
+            ),
+            ProgressEvent::UsingCached { path } => log::info!(
+                target: LOG_TARGET,
+                "event=using_cached path={}",
+                path.display()
+            ),
+            ProgressEvent::ExtractStarted { entry, dest } => log::info!(
+                target: LOG_TARGET,
+                "event=extract_started entry={} dest={}",
+                entry,
+                dest.display()
+            ),
+            ProgressEvent::ExtractFinished { dest } => log::info!(
+                target: LOG_TARGET,
+                "event=extract_finished dest={}",
+                dest.display()
+            ),
+            ProgressEvent::RouteProgress { visited, total } => log::trace!(
+                target: LOG_TARGET,
+                "event=route_progress visited={} total={}",
+                visited,
+                total
+            ),
+            ProgressEvent::RouteFinished { length } => log::info!(
+                target: LOG_TARGET,
+                "event=route_finished length={}",
+                length
+            ),
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Routes for a ship whose tank cannot cover the whole trip: jump-drive hops are limited by the
+//! fuel left, and the ship fills up at refuel points such as stations and SSUs on the way.
+//!
+//! Refuel points come from a text file with one system per line, by name or id. Anything after
+//! the first comma is a free-form label, and blank lines and lines starting with `#` are
+//! skipped:
+//!
+//! ```text
+//! # system, what is there
+//! P:STK3, station
+//! 30000142, SSU "Depot"
+//! ```
+
+use crate::db::{find_system, System, LIGHT_YEAR};
+use crate::error::{Error, ErrorKind, Result, ResultExt};
+use crate::graph::HopKind;
+use crate::path::{route_hops, Hop};
+use crate::ship::ShipProfile;
+use crate::starmap::Starmap;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs;
+use std::path::Path;
+
+/// Slack for comparing fuel amounts that went through floating-point sums.
+const FUEL_EPSILON: f64 = 1e-9;
+
+/// Read the refuel points listed in the file at `path`, as system indices in file order.
+pub fn load_refuel_points(path: &Path, systems: &[System]) -> Result<Vec<usize>> {
+    let text = fs::read_to_string(path).context(ErrorKind::Io, || {
+        format!("failed to read refuel points {}", path.display())
+    })?;
+    parse_refuel_points(&text, systems).context(ErrorKind::InvalidInput, || {
+        format!("invalid refuel points file {}", path.display())
+    })
+}
+
+/// [`load_refuel_points`] for text already in memory.
+pub fn parse_refuel_points(text: &str, systems: &[System]) -> Result<Vec<usize>> {
+    let mut points = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let system = line.split(',').next().unwrap_or_default().trim();
+        let idx = find_system(systems, system)
+            .or_else(|err| {
+                system
+                    .parse::<i64>()
+                    .ok()
+                    .and_then(|id| systems.iter().position(|s| s.id == id))
+                    .ok_or(err)
+            })
+            .context(ErrorKind::InvalidInput, || format!("line {}", number + 1))?;
+        if !points.contains(&idx) {
+            points.push(idx);
+        }
+    }
+    Ok(points)
+}
+
+/// A stop to fill the tank.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RefuelStop {
+    /// Position of the stop in [`RefuelRoute::route`].
+    pub position: usize,
+    pub system: usize,
+    /// Fuel left on arrival; the ship leaves with a full tank.
+    pub fuel_left: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RefuelRoute {
+    pub route: Vec<usize>,
+    pub hops: Vec<Hop>,
+    /// Fuel burned on each hop; 0 for gates.
+    pub fuel_per_hop: Vec<f64>,
+    /// Fuel left after each hop, before any refuelling at its end.
+    pub fuel_left: Vec<f64>,
+    /// The fewest stops that make the route feasible, each as late as possible.
+    pub stops: Vec<RefuelStop>,
+    pub total_fuel: f64,
+}
+
+/// A partial route in the search: `burned` fuel so far and `left` in the tank when leaving
+/// `system`, after filling up there if it is a refuel point.
+struct Label {
+    burned: f64,
+    hops: usize,
+    left: f64,
+    system: usize,
+    parent: Option<usize>,
+}
+
+/// A label waiting in the queue, least fuel burned first, then fewest hops.
+struct Queued {
+    burned: f64,
+    hops: usize,
+    label: usize,
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .burned
+            .total_cmp(&self.burned)
+            .then(other.hops.cmp(&self.hops))
+            .then(other.label.cmp(&self.label))
+    }
+}
+
+/// The route from `start` to `goal` that burns the least fuel, then has the fewest hops, for a
+/// ship that leaves `start` with a full tank and may fill up at `refuel_points`. Gates burn no
+/// fuel; jump-drive hops are limited to the ship's range and to the fuel left.
+///
+/// Fails with [`Error::NoRoute`] when no such route exists, including when every route needs
+/// more fuel between refuel points than the tank holds, and with an invalid-input error when
+/// `start`, `goal` or a refuel point is not a system index of `starmap`.
+pub fn refuel_route(
+    starmap: &Starmap,
+    ship: &ShipProfile,
+    refuel_points: &[usize],
+    start: usize,
+    goal: usize,
+) -> Result<RefuelRoute> {
+    let systems = &starmap.systems;
+    let named = [("start", start), ("goal", goal)];
+    let points = refuel_points.iter().map(|&p| ("refuel point", p));
+    if let Some((what, index)) = named
+        .into_iter()
+        .chain(points)
+        .find(|&(_, i)| i >= systems.len())
+    {
+        return Err(Error::invalid_input(format!(
+            "{} {} is not a system index; the star map has {} systems",
+            what,
+            index,
+            systems.len()
+        )));
+    }
+    let graph = starmap.jump_graph(ship.effective_range())?;
+    let mut refuel = vec![false; systems.len()];
+    for &p in refuel_points {
+        refuel[p] = true;
+    }
+    let capacity = ship.fuel_capacity;
+    let light_years = |a: usize, b: usize| match (systems[a].position, systems[b].position) {
+        (Some(pa), Some(pb)) => pa.distance(pb) / LIGHT_YEAR,
+        _ => 0.0,
+    };
+
+    // Refuelling is free, so the search fills up at every refuel point it passes and only
+    // [`plan_stops`] decides which stops are needed. Labels are settled least fuel first, so a
+    // label at a system is worth keeping only if it has more fuel left than every earlier one.
+    let mut labels = vec![Label {
+        burned: 0.0,
+        hops: 0,
+        left: capacity,
+        system: start,
+        parent: None,
+    }];
+    let mut best_left = vec![f64::NEG_INFINITY; systems.len()];
+    let mut queue = BinaryHeap::from([Queued {
+        burned: 0.0,
+        hops: 0,
+        label: 0,
+    }]);
+    let mut found = None;
+    while let Some(Queued { label: id, .. }) = queue.pop() {
+        let Label {
+            burned,
+            hops,
+            left,
+            system,
+            ..
+        } = labels[id];
+        if left <= best_left[system] + FUEL_EPSILON {
+            continue;
+        }
+        best_left[system] = left;
+        if system == goal {
+            found = Some(id);
+            break;
+        }
+        for edge in graph.edges(NodeIndex::new(system)) {
+            let next = if edge.source().index() == system {
+                edge.target().index()
+            } else {
+                edge.source().index()
+            };
+            let cost = match edge.weight() {
+                HopKind::Gate => 0.0,
+                HopKind::JumpDrive => ship.fuel_for(light_years(system, next)),
+            };
+            if cost > left + FUEL_EPSILON {
+                continue;
+            }
+            let next_left = if refuel[next] { capacity } else { left - cost };
+            if next_left <= best_left[next] + FUEL_EPSILON {
+                continue;
+            }
+            labels.push(Label {
+                burned: burned + cost,
+                hops: hops + 1,
+                left: next_left,
+                system: next,
+                parent: Some(id),
+            });
+            queue.push(Queued {
+                burned: burned + cost,
+                hops: hops + 1,
+                label: labels.len() - 1,
+            });
+        }
+    }
+
+    let Some(mut id) = found else {
+        return Err(Error::NoRoute {
+            from: systems[start].name.clone(),
+            to: systems[goal].name.clone(),
+        });
+    };
+    let mut route = vec![labels[id].system];
+    while let Some(parent) = labels[id].parent {
+        route.push(labels[parent].system);
+        id = parent;
+    }
+    route.reverse();
+
+    let hops = route_hops(systems, &graph, &route);
+    let fuel_per_hop = ship.fuel_use(&hops).per_hop;
+    let (stops, fuel_left) = plan_stops(&route, &fuel_per_hop, &refuel, capacity);
+    Ok(RefuelRoute {
+        total_fuel: fuel_per_hop.iter().sum(),
+        route,
+        hops,
+        fuel_per_hop,
+        fuel_left,
+        stops,
+    })
+}
+
+/// The fewest refuel stops along a feasible route: drive on while the tank allows, and when the
+/// next hop is out of reach, fill up at the last refuel point passed. Filling up as late as
+/// possible never needs more stops than any other choice. Also returns the fuel left after
+/// each hop.
+fn plan_stops(
+    route: &[usize],
+    fuel_per_hop: &[f64],
+    refuel: &[bool],
+    capacity: f64,
+) -> (Vec<RefuelStop>, Vec<f64>) {
+    let mut stops = Vec::new();
+    let mut arrived = vec![capacity; route.len()];
+    let mut fuel = capacity;
+    let mut last_point = None;
+    for (i, &cost) in fuel_per_hop.iter().enumerate() {
+        if refuel[route[i]] {
+            last_point = Some(i);
+        }
+        if cost > fuel + FUEL_EPSILON {
+            let position = last_point
+                .take()
+                .expect("the search only returns routes that refuelling makes feasible");
+            stops.push(RefuelStop {
+                position,
+                system: route[position],
+                fuel_left: arrived[position],
+            });
+            fuel = capacity;
+            for j in position..i {
+                fuel -= fuel_per_hop[j];
+                arrived[j + 1] = fuel;
+            }
+        }
+        fuel -= cost;
+        arrived[i + 1] = fuel;
+    }
+    arrived.remove(0);
+    (stops, arrived)
+}
@@ -0,0 +1,117 @@
+//! Ship profiles: how far a ship's jump drive reaches and how much fuel it burns, configured as
+//! `[[ships]]` tables in the config file:
+//!
+//! ```toml
+//! [[ships]]
+//! name = "Reflex"
+//! jump_range = 25.0
+//! fuel_capacity = 1750.0
+//! fuel_per_ly = 0.01
+//! mass = 9750.0
+//! ```
+//!
+//! Gates cost no fuel. A jump-drive hop of `d` light-years burns `d * fuel_per_ly * mass`, so a
+//! heavier ship burns more for the same distance.
+
+use crate::error::{Error, Result};
+use crate::graph::HopKind;
+use crate::path::Hop;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShipProfile {
+    pub name: String,
+    /// The longest single jump-drive hop, in light-years.
+    pub jump_range: f64,
+    /// Fuel the ship holds when full.
+    pub fuel_capacity: f64,
+    /// Fuel burned per light-year for each tonne of mass.
+    pub fuel_per_ly: f64,
+    /// Mass in tonnes.
+    pub mass: f64,
+}
+
+impl ShipProfile {
+    /// Check that every number is usable: a positive range and mass, no negative fuel, and a
+    /// tank with room for at least some fuel when jumps burn any.
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::invalid_input("ship name is empty"));
+        }
+        let checks = [
+            ("jump_range", self.jump_range, self.jump_range > 0.0),
+            (
+                "fuel_capacity",
+                self.fuel_capacity,
+                self.fuel_capacity >= 0.0,
+            ),
+            ("fuel_per_ly", self.fuel_per_ly, self.fuel_per_ly >= 0.0),
+            ("mass", self.mass, self.mass > 0.0),
+        ];
+        for (key, value, valid) in checks {
+            if !valid || !value.is_finite() {
+                return Err(Error::invalid_input(format!(
+                    "ship {}: {} = {} is out of range",
+                    self.name, key, value
+                )));
+            }
+        }
+        if self.fuel_capacity == 0.0 && self.fuel_per_ly > 0.0 {
+            return Err(Error::invalid_input(format!(
+                "ship {}: fuel_capacity = 0 leaves no fuel for a single jump; set fuel_per_ly = 0 \
+                 for a ship whose jumps burn none",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Fuel burned by a jump-drive hop of `light_years`.
+    pub fn fuel_for(&self, light_years: f64) -> f64 {
+        light_years * self.fuel_per_ly * self.mass
+    }
+
+    /// The longest hop the ship can make: its jump range, or less when a full tank cannot
+    /// cover that far.
+    pub fn effective_range(&self) -> f64 {
+        let per_ly = self.fuel_per_ly * self.mass;
+        if per_ly > 0.0 {
+            self.jump_range.min(self.fuel_capacity / per_ly)
+        } else {
+            self.jump_range
+        }
+    }
+
+    /// Fuel burned on each hop of a route, and in total.
+    pub fn fuel_use(&self, hops: &[Hop]) -> FuelUse {
+        let per_hop: Vec<f64> = hops
+            .iter()
+            .map(|hop| match (hop.kind, hop.light_years) {
+                (HopKind::JumpDrive, Some(ly)) => self.fuel_for(ly),
+                _ => 0.0,
+            })
+            .collect();
+        let total = per_hop.iter().sum();
+        FuelUse {
+            per_hop,
+            total,
+            capacity: self.fuel_capacity,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FuelUse {
+    /// Fuel for each hop, in route order; 0 for gates.
+    pub per_hop: Vec<f64>,
+    pub total: f64,
+    pub capacity: f64,
+}
+
+impl FuelUse {
+    /// Whether the route needs more fuel than one full tank.
+    pub fn exceeds_capacity(&self) -> bool {
+        self.total > self.capacity
+    }
+}
@@ -0,0 +1,160 @@
+//! Binary snapshots of a loaded [`Starmap`], stored beside the database as
+//! `<db>.snapshot.bin` so later runs skip SQLite and graph construction.
+//!
+//! A snapshot starts with a magic number and a header naming the snapshot format version and
+//! the size, modification time and SHA-256 digest of the database it was built from, and the
+//! [`SchemaMapping`] it was read with. It is used only when the version and mapping match and
+//! the database is unchanged: same size, and either the same modification time or, failing
+//! that, the same digest.
+
+use crate::config::SchemaMapping;
+use crate::db::{sha256_file, Jump, System};
+use crate::error::{ErrorKind, Result, ResultExt};
+use crate::graph::StarGraph;
+use crate::starmap::Starmap;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufReader, BufWriter, ErrorKind as IoErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const MAGIC: &[u8; 8] = b"EFSNAP\0\0";
+
+/// Version of the snapshot layout. Bump it whenever [`System`], [`Jump`] or the graph change
+/// shape so older snapshots are rebuilt instead of misread.
+pub const SNAPSHOT_VERSION: u32 = 5;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    db_sha256: String,
+    db_size: u64,
+    /// Seconds and nanoseconds since the Unix epoch.
+    db_modified: Option<(u64, u32)>,
+    /// [`SchemaMapping::fingerprint`] of the mapping the star map was read with.
+    mapping: String,
+}
+
+#[derive(Serialize)]
+struct BodyRef<'a> {
+    systems: &'a [System],
+    jumps: &'a [Jump],
+    graph: &'a StarGraph,
+}
+
+#[derive(Deserialize)]
+struct Body {
+    systems: Vec<System>,
+    jumps: Vec<Jump>,
+    graph: StarGraph,
+}
+
+/// Where the snapshot of the database at `db` is stored.
+pub fn snapshot_path(db: &Path) -> PathBuf {
+    let mut name = db.as_os_str().to_os_string();
+    name.push(".snapshot.bin");
+    PathBuf::from(name)
+}
+
+fn db_stat(db: &Path) -> Result<(u64, Option<(u64, u32)>)> {
+    let meta =
+        fs::metadata(db).context(ErrorKind::Io, || format!("failed to stat {}", db.display()))?;
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| (d.as_secs(), d.subsec_nanos()));
+    Ok((meta.len(), modified))
+}
+
+/// Load the snapshot of `db` if there is one and it is fresh. Returns `Ok(None)` when the
+/// snapshot is missing, from another format version, built from a different database or read
+/// with a different `mapping`.
+pub fn read_snapshot(db: &Path, mapping: &SchemaMapping) -> Result<Option<Starmap>> {
+    let path = snapshot_path(db);
+    let file = match fs::File::open(&path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == IoErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err).context(ErrorKind::Io, || {
+                format!("failed to open snapshot {}", path.display())
+            })
+        }
+    };
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 8];
+    if reader.read_exact(&mut magic).is_err() || &magic != MAGIC {
+        return Ok(None);
+    }
+    let header: Header = bincode::deserialize_from(&mut reader).context(ErrorKind::Io, || {
+        format!("corrupt snapshot header in {}", path.display())
+    })?;
+    if header.version != SNAPSHOT_VERSION || header.mapping != mapping.fingerprint() {
+        return Ok(None);
+    }
+
+    let (size, modified) = db_stat(db)?;
+    if header.db_size != size {
+        return Ok(None);
+    }
+    let rehashed = modified.is_none() || header.db_modified != modified;
+    if rehashed && header.db_sha256 != sha256_file(db)? {
+        return Ok(None);
+    }
+
+    let body: Body = bincode::deserialize_from(&mut reader).context(ErrorKind::Io, || {
+        format!("corrupt snapshot {}", path.display())
+    })?;
+    drop(reader);
+    let starmap = Starmap {
+        systems: body.systems,
+        jumps: body.jumps,
+        graph: body.graph,
+    };
+
+    // The database was copied or touched without changing; record its new modification time
+    // so the next load skips hashing it. Best effort, like the snapshot itself.
+    if rehashed && modified.is_some() {
+        let header = Header {
+            db_modified: modified,
+            ..header
+        };
+        let _ = write_file(&path, &header, &starmap);
+    }
+    Ok(Some(starmap))
+}
+
+/// Write the snapshot of `starmap`, loaded from `db` with `mapping`, atomically replacing any
+/// older one.
+pub fn write_snapshot(db: &Path, mapping: &SchemaMapping, starmap: &Starmap) -> Result<()> {
+    let (db_size, db_modified) = db_stat(db)?;
+    let header = Header {
+        version: SNAPSHOT_VERSION,
+        db_sha256: sha256_file(db)?,
+        db_size,
+        db_modified,
+        mapping: mapping.fingerprint(),
+    };
+    write_file(&snapshot_path(db), &header, starmap)
+}
+
+fn write_file(path: &Path, header: &Header, starmap: &Starmap) -> Result<()> {
+    let body = BodyRef {
+        systems: &starmap.systems,
+        jumps: &starmap.jumps,
+        graph: &starmap.graph,
+    };
+    let tmp = path.with_extension("bin.tmp");
+    let write_err = || format!("failed to write snapshot {}", tmp.display());
+    let file = fs::File::create(&tmp).context(ErrorKind::Io, write_err)?;
+    let mut writer = BufWriter::new(file);
+    writer.write_all(MAGIC).context(ErrorKind::Io, write_err)?;
+    bincode::serialize_into(&mut writer, header).context(ErrorKind::Io, write_err)?;
+    bincode::serialize_into(&mut writer, &body).context(ErrorKind::Io, write_err)?;
+    writer.flush().context(ErrorKind::Io, write_err)?;
+    drop(writer);
+    fs::rename(&tmp, path).context(ErrorKind::Io, || {
+        format!("failed to move snapshot to {}", path.display())
+    })
+}
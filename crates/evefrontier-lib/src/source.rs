@@ -0,0 +1,232 @@
+//! Where a star map comes from. The SQLite dataset is one [`StarmapSource`]; star maps
+//! published by community tools as JSON or CSV are others.
+//!
+//! JSON sources hold one object with `systems` and `jumps` arrays:
+//!
+//! ```json
+//! {
+//!   "systems": [
+//!     { "id": 30000001, "name": "A 2560", "region_id": 10000001, "region": "Region",
+//!       "constellation_id": 20000001, "constellation": "Constellation",
+//!       "position": { "x": 1.0e17, "y": -2.0e16, "z": 3.5e16 } }
+//!   ],
+//!   "jumps": [ { "from_id": 30000001, "to_id": 30000002 } ]
+//! }
+//! ```
+//!
+//! Only `id` and `name` are required for a system.
+//!
+//! CSV sources are two files with header rows: systems with the columns `id`, `name` and the
+//! optional `region_id`, `region`, `constellation_id`, `constellation`, `x`, `y`, `z`, and jumps
+//! with `from_id` and `to_id`. Given `map.csv` the jumps are read from `map.jumps.csv`; given a
+//! directory, from `systems.csv` and `jumps.csv` inside it. Coordinates are in metres.
+
+use crate::config::SchemaMapping;
+use crate::db::{self, Jump, Position, System};
+use crate::error::{Error, ErrorKind, Result, ResultExt};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Something that can produce the systems and jumps of a star map.
+pub trait StarmapSource {
+    fn load(&self) -> Result<(Vec<System>, Vec<Jump>)>;
+}
+
+/// The file formats [`open_source`] understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceFormat {
+    Sqlite,
+    Json,
+    Csv,
+}
+
+impl SourceFormat {
+    /// Guess the format from the extension of `path`; directories are CSV sources.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        if path.is_dir() {
+            return Some(SourceFormat::Csv);
+        }
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "db" | "sqlite" | "sqlite3" => Some(SourceFormat::Sqlite),
+            "json" => Some(SourceFormat::Json),
+            "csv" => Some(SourceFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Open `path` as a star map source of `format`, or of the format its extension suggests.
+/// Paths with an unrecognised extension are treated as SQLite databases.
+pub fn open_source(path: &Path, format: Option<SourceFormat>) -> Box<dyn StarmapSource> {
+    open_source_with(path, format, &SchemaMapping::default())
+}
+
+/// [`open_source`], reading SQLite databases with `mapping`. Other formats have fixed field
+/// names and ignore it.
+pub fn open_source_with(
+    path: &Path,
+    format: Option<SourceFormat>,
+    mapping: &SchemaMapping,
+) -> Box<dyn StarmapSource> {
+    match resolve_format(path, format) {
+        SourceFormat::Sqlite => Box::new(SqliteSource::new(path).with_mapping(mapping.clone())),
+        SourceFormat::Json => Box::new(JsonSource::new(path)),
+        SourceFormat::Csv => Box::new(CsvSource::from_path(path)),
+    }
+}
+
+pub(crate) fn resolve_format(path: &Path, format: Option<SourceFormat>) -> SourceFormat {
+    format
+        .or_else(|| SourceFormat::from_path(path))
+        .unwrap_or(SourceFormat::Sqlite)
+}
+
+/// The SQLite `static_data.db` dataset, read with [`db::load_starmap_with`].
+#[derive(Debug, Clone)]
+pub struct SqliteSource {
+    path: PathBuf,
+    mapping: SchemaMapping,
+}
+
+impl SqliteSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        SqliteSource {
+            path: path.into(),
+            mapping: SchemaMapping::default(),
+        }
+    }
+
+    /// Read the tables and columns named in `mapping` instead of detecting them.
+    pub fn with_mapping(mut self, mapping: SchemaMapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+}
+
+impl StarmapSource for SqliteSource {
+    fn load(&self) -> Result<(Vec<System>, Vec<Jump>)> {
+        db::load_starmap_with(&self.path, &self.mapping)
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonStarmap {
+    systems: Vec<System>,
+    jumps: Vec<Jump>,
+}
+
+/// A JSON file in the format described in the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct JsonSource {
+    path: PathBuf,
+}
+
+impl JsonSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        JsonSource { path: path.into() }
+    }
+}
+
+impl StarmapSource for JsonSource {
+    fn load(&self) -> Result<(Vec<System>, Vec<Jump>)> {
+        let text = fs::read_to_string(&self.path).context(ErrorKind::Io, || {
+            format!("failed to read {}", self.path.display())
+        })?;
+        let starmap: JsonStarmap = serde_json::from_str(&text)
+            .context(ErrorKind::InvalidInput, || {
+                format!("{} is not a JSON star map", self.path.display())
+            })?;
+        Ok((starmap.systems, starmap.jumps))
+    }
+}
+
+#[derive(Deserialize)]
+struct CsvSystem {
+    id: i64,
+    name: String,
+    region_id: Option<i64>,
+    region: Option<String>,
+    constellation_id: Option<i64>,
+    constellation: Option<String>,
+    x: Option<f64>,
+    y: Option<f64>,
+    z: Option<f64>,
+}
+
+impl From<CsvSystem> for System {
+    fn from(row: CsvSystem) -> Self {
+        let position = match (row.x, row.y, row.z) {
+            (Some(x), Some(y), Some(z)) => Some(Position { x, y, z }),
+            _ => None,
+        };
+        System {
+            id: row.id,
+            name: row.name,
+            region_id: row.region_id,
+            region: row.region,
+            constellation_id: row.constellation_id,
+            constellation: row.constellation,
+            position,
+        }
+    }
+}
+
+/// A pair of CSV files in the format described in the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct CsvSource {
+    systems: PathBuf,
+    jumps: PathBuf,
+}
+
+impl CsvSource {
+    pub fn new(systems: impl Into<PathBuf>, jumps: impl Into<PathBuf>) -> Self {
+        CsvSource {
+            systems: systems.into(),
+            jumps: jumps.into(),
+        }
+    }
+
+    /// `dir/systems.csv` and `dir/jumps.csv` for a directory, otherwise `path` and the
+    /// `<stem>.jumps.csv` beside it.
+    pub fn from_path(path: &Path) -> Self {
+        if path.is_dir() {
+            return CsvSource::new(path.join("systems.csv"), path.join("jumps.csv"));
+        }
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("systems");
+        CsvSource::new(path, path.with_file_name(format!("{}.jumps.csv", stem)))
+    }
+
+    fn read<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Vec<T>> {
+        if !path.is_file() {
+            return Err(Error::invalid_input(format!(
+                "CSV source file {} does not exist",
+                path.display()
+            )));
+        }
+        let mut reader = csv::Reader::from_path(path).context(ErrorKind::Io, || {
+            format!("failed to open {}", path.display())
+        })?;
+        reader
+            .deserialize()
+            .collect::<std::result::Result<Vec<T>, _>>()
+            .context(ErrorKind::InvalidInput, || {
+                format!("{} does not match the CSV star map schema", path.display())
+            })
+    }
+}
+
+impl StarmapSource for CsvSource {
+    fn load(&self) -> Result<(Vec<System>, Vec<Jump>)> {
+        let systems = CsvSource::read::<CsvSystem>(&self.systems)?
+            .into_iter()
+            .map(System::from)
+            .collect();
+        let jumps = CsvSource::read::<Jump>(&self.jumps)?;
+        Ok((systems, jumps))
+    }
+}
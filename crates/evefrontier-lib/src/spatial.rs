@@ -0,0 +1,201 @@
+//! A KD-tree over system positions for straight-line queries: the systems closest to a point,
+//! and the systems within a radius of it. Systems without coordinates are left out.
+
+use crate::db::{Position, System, LIGHT_YEAR};
+use crate::error::{Error, Result};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// A system found by a [`SpatialIndex`] query.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Neighbour {
+    pub system: usize,
+    /// Straight-line distance from the query point.
+    pub light_years: f64,
+}
+
+/// A KD-tree stored as an array: each subslice holds its median in the middle, with smaller
+/// coordinates before it and larger ones after, splitting on x, y and z in turn.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    nodes: Vec<(usize, [f64; 3])>,
+}
+
+fn coordinates(p: Position) -> [f64; 3] {
+    [p.x, p.y, p.z]
+}
+
+fn squared_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum()
+}
+
+impl SpatialIndex {
+    /// Index the systems that have a position; results refer to them by index in `systems`.
+    pub fn new(systems: &[System]) -> Self {
+        let mut nodes: Vec<(usize, [f64; 3])> = systems
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.position.map(|p| (i, coordinates(p))))
+            .collect();
+        build(&mut nodes, 0);
+        SpatialIndex { nodes }
+    }
+
+    /// Number of indexed systems.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The `k` systems closest to `point`, nearest first; equally distant systems are ordered
+    /// by index.
+    pub fn nearest(&self, point: Position, k: usize) -> Vec<Neighbour> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        search_nearest(&self.nodes, 0, &coordinates(point), k, &mut best);
+        finish(best.into_vec())
+    }
+
+    /// Every system within `light_years` of `point`, nearest first. The radius must be a
+    /// finite, non-negative number.
+    pub fn within(&self, point: Position, light_years: f64) -> Result<Vec<Neighbour>> {
+        if !(light_years >= 0.0 && light_years.is_finite()) {
+            return Err(Error::invalid_input(format!(
+                "the radius must be a non-negative number of light-years, not {}",
+                light_years
+            )));
+        }
+        let radius = light_years * LIGHT_YEAR;
+        let mut found = Vec::new();
+        search_within(
+            &self.nodes,
+            0,
+            &coordinates(point),
+            radius * radius,
+            &mut found,
+        );
+        Ok(finish(found))
+    }
+}
+
+fn search_nearest(
+    nodes: &[(usize, [f64; 3])],
+    depth: usize,
+    point: &[f64; 3],
+    k: usize,
+    best: &mut BinaryHeap<Candidate>,
+) {
+    if nodes.is_empty() {
+        return;
+    }
+    let mid = nodes.len() / 2;
+    let (system, at) = nodes[mid];
+    let axis = depth % 3;
+    best.push(Candidate {
+        squared: squared_distance(point, &at),
+        system,
+    });
+    if best.len() > k {
+        best.pop();
+    }
+    let offset = point[axis] - at[axis];
+    let (near, far) = if offset < 0.0 {
+        (&nodes[..mid], &nodes[mid + 1..])
+    } else {
+        (&nodes[mid + 1..], &nodes[..mid])
+    };
+    search_nearest(near, depth + 1, point, k, best);
+    // The far side can only hold a closer system if the splitting plane is closer than the
+    // worst kept so far; `<=` keeps ties for the lower index.
+    if best.len() < k
+        || best
+            .peek()
+            .is_some_and(|worst| offset * offset <= worst.squared)
+    {
+        search_nearest(far, depth + 1, point, k, best);
+    }
+}
+
+fn build(nodes: &mut [(usize, [f64; 3])], depth: usize) {
+    if nodes.len() <= 1 {
+        return;
+    }
+    let axis = depth % 3;
+    let mid = nodes.len() / 2;
+    nodes.select_nth_unstable_by(mid, |a, b| a.1[axis].total_cmp(&b.1[axis]));
+    let (left, right) = nodes.split_at_mut(mid);
+    build(left, depth + 1);
+    build(&mut right[1..], depth + 1);
+}
+
+fn search_within(
+    nodes: &[(usize, [f64; 3])],
+    depth: usize,
+    point: &[f64; 3],
+    squared_radius: f64,
+    found: &mut Vec<Candidate>,
+) {
+    if nodes.is_empty() {
+        return;
+    }
+    let mid = nodes.len() / 2;
+    let (system, at) = nodes[mid];
+    let squared = squared_distance(point, &at);
+    if squared <= squared_radius {
+        found.push(Candidate { squared, system });
+    }
+    let offset = point[depth % 3] - at[depth % 3];
+    if offset <= 0.0 || offset * offset <= squared_radius {
+        search_within(&nodes[..mid], depth + 1, point, squared_radius, found);
+    }
+    if offset >= 0.0 || offset * offset <= squared_radius {
+        search_within(&nodes[mid + 1..], depth + 1, point, squared_radius, found);
+    }
+}
+
+/// A system under consideration, ordered by distance and then index so that the heap in
+/// [`SpatialIndex::nearest`] evicts the farthest, highest-indexed one first.
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    squared: f64,
+    system: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.squared
+            .total_cmp(&other.squared)
+            .then(self.system.cmp(&other.system))
+    }
+}
+
+fn finish(mut found: Vec<Candidate>) -> Vec<Neighbour> {
+    found.sort_unstable();
+    found
+        .into_iter()
+        .map(|c| Neighbour {
+            system: c.system,
+            light_years: c.squared.sqrt() / LIGHT_YEAR,
+        })
+        .collect()
+}
@@ -0,0 +1,107 @@
+use crate::config::SchemaMapping;
+use crate::db::{self, Jump, System};
+use crate::error::Result;
+use crate::graph::{build_graph, build_graph_with_jump_range, StarGraph};
+use crate::snapshot;
+use crate::source::{self, SourceFormat, StarmapSource};
+use rusqlite::Connection;
+use std::path::Path;
+
+/// The systems and jumps of a dataset together with the graph built from them.
+#[derive(Debug, Clone)]
+pub struct Starmap {
+    pub systems: Vec<System>,
+    pub jumps: Vec<Jump>,
+    pub graph: StarGraph,
+}
+
+impl Starmap {
+    pub fn new(systems: Vec<System>, jumps: Vec<Jump>) -> Self {
+        let graph = build_graph(&systems, &jumps);
+        Starmap {
+            systems,
+            jumps,
+            graph,
+        }
+    }
+
+    /// A graph of the gates plus jump-drive hops between systems at most `light_years` apart;
+    /// see [`build_graph_with_jump_range`]. `self.graph` keeps the gates only.
+    pub fn jump_graph(&self, light_years: f64) -> Result<StarGraph> {
+        build_graph_with_jump_range(&self.systems, &self.jumps, light_years)
+    }
+
+    /// Read the star map from the SQLite database at `path`, ignoring any snapshot.
+    pub fn from_db(path: &Path) -> Result<Self> {
+        Starmap::from_db_with(path, &SchemaMapping::default())
+    }
+
+    /// [`Starmap::from_db`] with a [`SchemaMapping`].
+    pub fn from_db_with(path: &Path, mapping: &SchemaMapping) -> Result<Self> {
+        let (systems, jumps) = db::load_starmap_with(path, mapping)?;
+        Ok(Starmap::new(systems, jumps))
+    }
+
+    /// Read the star map through a connection the caller already holds, such as an in-memory
+    /// database. No snapshot is involved.
+    pub fn from_connection(conn: &Connection) -> Result<Self> {
+        let (systems, jumps) = db::load_starmap_from_connection(conn)?;
+        Ok(Starmap::new(systems, jumps))
+    }
+
+    /// Read the star map from any [`StarmapSource`].
+    pub fn from_source(source: &dyn StarmapSource) -> Result<Self> {
+        let (systems, jumps) = source.load()?;
+        Ok(Starmap::new(systems, jumps))
+    }
+
+    /// Load the star map at `path` in `format`, or in the format its extension suggests.
+    /// SQLite databases go through [`Starmap::load`] and its snapshot; JSON and CSV sources
+    /// are read directly.
+    pub fn open(path: &Path, format: Option<SourceFormat>) -> Result<Self> {
+        Starmap::open_with(path, format, &SchemaMapping::default())
+    }
+
+    /// [`Starmap::open`], reading SQLite databases with `mapping`.
+    pub fn open_with(
+        path: &Path,
+        format: Option<SourceFormat>,
+        mapping: &SchemaMapping,
+    ) -> Result<Self> {
+        match source::resolve_format(path, format) {
+            SourceFormat::Sqlite => Starmap::load_with(path, mapping),
+            _ => Starmap::from_source(source::open_source(path, format).as_ref()),
+        }
+    }
+
+    /// Load the star map for the database at `path`, from its snapshot when the snapshot is
+    /// fresh. Otherwise the database is read and the snapshot rewritten; failing to write it
+    /// (for example in a read-only data directory) only costs the next start its speed-up.
+    pub fn load(path: &Path) -> Result<Self> {
+        Starmap::load_with(path, &SchemaMapping::default())
+    }
+
+    /// [`Starmap::load`] with a [`SchemaMapping`]. Snapshots record the mapping they were built
+    /// with, so changing it rebuilds the snapshot.
+    pub fn load_with(path: &Path, mapping: &SchemaMapping) -> Result<Self> {
+        match snapshot::read_snapshot(path, mapping) {
+            Ok(Some(starmap)) => return Ok(starmap),
+            Ok(None) => {}
+            Err(err) => log::warn!(
+                "ignoring unreadable snapshot {}: {}",
+                snapshot::snapshot_path(path).display(),
+                err
+            ),
+        }
+
+        let starmap = Starmap::from_db_with(path, mapping)?;
+        if let Err(err) = snapshot::write_snapshot(path, mapping, &starmap) {
+            log::warn!(
+                "could not write snapshot {}: {}",
+                snapshot::snapshot_path(path).display(),
+                err
+            );
+        }
+        Ok(starmap)
+    }
+}
@@ -0,0 +1,223 @@
+//! Whole-map statistics of the gate network, for sanity-checking dataset releases: counts, the
+//! degree distribution, distances in jumps and a breakdown per region.
+//!
+//! Distances need a breadth-first search from every system. With the `parallel` feature they
+//! run on the rayon thread pool.
+
+use crate::components::{connected_components, region_label};
+use crate::graph::simple_neighbours;
+use crate::starmap::Starmap;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphStats {
+    pub systems: usize,
+    /// Distinct gate connections; a gate listed in both directions counts once.
+    pub jumps: usize,
+    pub components: usize,
+    pub largest_component: usize,
+    /// Systems without any gate.
+    pub isolated_systems: usize,
+    pub degree: DegreeStats,
+    /// The most jumps between two systems that can reach each other.
+    pub diameter: usize,
+    /// The smallest eccentricity in the largest component: the jumps from its most central
+    /// system to the farthest one. Other components are too small to matter here.
+    pub radius: usize,
+    /// Mean jumps over every ordered pair of distinct systems that can reach each other; 0 when
+    /// there are none.
+    pub average_path_length: f64,
+    /// Ordered by region label; systems without a region come first.
+    pub regions: Vec<RegionStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DegreeStats {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    /// How many systems have each degree, in ascending order of degree.
+    pub distribution: Vec<DegreeCount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DegreeCount {
+    pub degree: usize,
+    pub systems: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RegionStats {
+    /// The region name, its id when the name is unknown, or `None` for systems without one.
+    pub region: Option<String>,
+    pub systems: usize,
+    /// Gates between two systems of the region.
+    pub internal_jumps: usize,
+    /// Gates from the region to another one.
+    pub border_jumps: usize,
+    /// Pieces the region falls into using its internal gates only.
+    pub components: usize,
+    pub average_degree: f64,
+}
+
+/// Compute [`GraphStats`] for the star map.
+pub fn graph_stats(starmap: &Starmap) -> GraphStats {
+    let graph = &starmap.graph;
+    let n = graph.node_count();
+    let neighbours = simple_neighbours(graph);
+    let edges: Vec<(usize, usize)> = neighbours
+        .iter()
+        .enumerate()
+        .flat_map(|(v, next)| next.iter().filter(move |&&w| v < w).map(move |&w| (v, w)))
+        .collect();
+
+    let components = connected_components(graph);
+    let distances = distances_from_every_system(&neighbours);
+    let diameter = distances.iter().map(|d| d.eccentricity).max().unwrap_or(0);
+    let radius = components
+        .first()
+        .and_then(|largest| largest.iter().map(|&v| distances[v].eccentricity).min())
+        .unwrap_or(0);
+    let pairs: u64 = distances.iter().map(|d| d.reached).sum();
+    let total: u64 = distances.iter().map(|d| d.total).sum();
+    let average_path_length = if pairs > 0 {
+        total as f64 / pairs as f64
+    } else {
+        0.0
+    };
+
+    let degrees: Vec<usize> = neighbours.iter().map(Vec::len).collect();
+    let mut distribution: BTreeMap<usize, usize> = BTreeMap::new();
+    for &d in &degrees {
+        *distribution.entry(d).or_default() += 1;
+    }
+
+    GraphStats {
+        systems: n,
+        jumps: edges.len(),
+        components: components.len(),
+        largest_component: components.first().map_or(0, Vec::len),
+        isolated_systems: degrees.iter().filter(|&&d| d == 0).count(),
+        degree: DegreeStats {
+            min: degrees.iter().copied().min().unwrap_or(0),
+            max: degrees.iter().copied().max().unwrap_or(0),
+            mean: mean(degrees.iter().sum(), n),
+            distribution: distribution
+                .into_iter()
+                .map(|(degree, systems)| DegreeCount { degree, systems })
+                .collect(),
+        },
+        diameter,
+        radius,
+        average_path_length,
+        regions: region_stats(starmap, &neighbours, &edges),
+    }
+}
+
+fn mean(sum: usize, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        sum as f64 / count as f64
+    }
+}
+
+fn region_stats(
+    starmap: &Starmap,
+    neighbours: &[Vec<usize>],
+    edges: &[(usize, usize)],
+) -> Vec<RegionStats> {
+    let labels: Vec<Option<String>> = starmap.systems.iter().map(region_label).collect();
+    let mut members: BTreeMap<&Option<String>, Vec<usize>> = BTreeMap::new();
+    for (v, label) in labels.iter().enumerate() {
+        members.entry(label).or_default().push(v);
+    }
+    let mut internal: BTreeMap<&Option<String>, usize> = BTreeMap::new();
+    let mut border: BTreeMap<&Option<String>, usize> = BTreeMap::new();
+    for &(a, b) in edges {
+        if labels[a] == labels[b] {
+            *internal.entry(&labels[a]).or_default() += 1;
+        } else {
+            *border.entry(&labels[a]).or_default() += 1;
+            *border.entry(&labels[b]).or_default() += 1;
+        }
+    }
+
+    members
+        .into_iter()
+        .map(|(label, systems)| {
+            let mut seen = vec![false; neighbours.len()];
+            let mut components = 0;
+            for &start in &systems {
+                if seen[start] {
+                    continue;
+                }
+                components += 1;
+                seen[start] = true;
+                let mut stack = vec![start];
+                while let Some(v) = stack.pop() {
+                    for &w in &neighbours[v] {
+                        if !seen[w] && labels[w] == *label {
+                            seen[w] = true;
+                            stack.push(w);
+                        }
+                    }
+                }
+            }
+            let degree_sum = systems.iter().map(|&v| neighbours[v].len()).sum();
+            RegionStats {
+                region: label.clone(),
+                systems: systems.len(),
+                internal_jumps: internal.get(label).copied().unwrap_or(0),
+                border_jumps: border.get(label).copied().unwrap_or(0),
+                components,
+                average_degree: mean(degree_sum, systems.len()),
+            }
+        })
+        .collect()
+}
+
+/// What one breadth-first search finds.
+struct Distances {
+    /// Jumps to the farthest reachable system.
+    eccentricity: usize,
+    /// Other systems reached, and the jumps to all of them together.
+    reached: u64,
+    total: u64,
+}
+
+fn distances_from_every_system(neighbours: &[Vec<usize>]) -> Vec<Distances> {
+    let search = |start: usize| {
+        let mut dist = vec![usize::MAX; neighbours.len()];
+        let mut queue = VecDeque::from([start]);
+        dist[start] = 0;
+        let mut found = Distances {
+            eccentricity: 0,
+            reached: 0,
+            total: 0,
+        };
+        while let Some(v) = queue.pop_front() {
+            found.eccentricity = dist[v];
+            for &w in &neighbours[v] {
+                if dist[w] == usize::MAX {
+                    dist[w] = dist[v] + 1;
+                    found.reached += 1;
+                    found.total += dist[w] as u64;
+                    queue.push_back(w);
+                }
+            }
+        }
+        found
+    };
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        (0..neighbours.len()).into_par_iter().map(search).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (0..neighbours.len()).map(search).collect()
+    }
+}
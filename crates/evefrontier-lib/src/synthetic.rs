@@ -0,0 +1,406 @@
+//! Seeded random galaxies for tests and benchmarks that need more than the three-system
+//! fixture.
+//!
+//! Systems are grouped into constellations and constellations into regions, each clustered in
+//! space. Gates follow the same hierarchy as the real map: every constellation is internally
+//! connected through short gates between nearby systems, constellations in a region are linked
+//! by a few gates between their closest systems, and regions are linked sparsely to their
+//! nearest neighbours. The result is always a single connected component. The same
+//! [`GalaxyConfig`] (including its seed) always produces the same galaxy.
+
+use crate::db::{Jump, Position, System, LIGHT_YEAR};
+use crate::error::{ErrorKind, Result, ResultExt};
+use crate::starmap::Starmap;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rusqlite::{params, Connection};
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+
+const FIRST_REGION_ID: i64 = 10_000_001;
+const FIRST_CONSTELLATION_ID: i64 = 20_000_001;
+const FIRST_SYSTEM_ID: i64 = 30_000_001;
+
+/// Shape of a generated galaxy.
+#[derive(Debug, Clone)]
+pub struct GalaxyConfig {
+    pub seed: u64,
+    pub systems: usize,
+    pub regions: usize,
+    pub constellations_per_region: usize,
+    /// Chance that a system gets one extra gate to a nearby system in its constellation, on
+    /// top of the gates that keep the constellation connected. Clamped to 0..=1, and NaN counts
+    /// as 0.
+    pub extra_gate_probability: f64,
+    /// Chance that a pair of neighbouring constellations or regions gets a second link; clamped
+    /// like `extra_gate_probability`.
+    pub extra_link_probability: f64,
+}
+
+impl Default for GalaxyConfig {
+    fn default() -> Self {
+        GalaxyConfig {
+            seed: 0,
+            systems: 1_000,
+            regions: 10,
+            constellations_per_region: 8,
+            extra_gate_probability: 0.35,
+            extra_link_probability: 0.25,
+        }
+    }
+}
+
+/// Which of the supported SQLite schemas [`write_sqlite`] produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyntheticSchema {
+    /// `SolarSystems` with region, constellation and `centerX/Y/Z` columns, plus the `Regions`,
+    /// `Constellations` and `Jumps` tables.
+    #[default]
+    Full,
+    /// Only `SolarSystems(solarSystemId, name)` and `Jumps`.
+    Minimal,
+}
+
+struct Cluster {
+    id: i64,
+    name: String,
+    center: Position,
+    members: Vec<usize>,
+}
+
+/// A uniformly distributed point within `radius` of `center`.
+fn point_near(rng: &mut ChaCha8Rng, center: Position, radius: f64) -> Position {
+    loop {
+        let (x, y, z): (f64, f64, f64) = (
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        );
+        if x * x + y * y + z * z <= 1.0 {
+            return Position {
+                x: center.x + x * radius,
+                y: center.y + y * radius,
+                z: center.z + z * radius,
+            };
+        }
+    }
+}
+
+/// EVE Frontier style system names such as `Y:170N` or `EQ3-K9T`.
+fn system_name(rng: &mut ChaCha8Rng, taken: &mut HashSet<String>) -> String {
+    const LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    const DIGITS: &[u8] = b"0123456789";
+    loop {
+        let colon_style = rng.gen_bool(0.5);
+        let mut pick = |set: &[u8]| set[rng.gen_range(0..set.len())] as char;
+        let name = if colon_style {
+            format!(
+                "{}:{}{}{}{}",
+                pick(LETTERS),
+                pick(DIGITS),
+                pick(DIGITS),
+                pick(DIGITS),
+                pick(LETTERS)
+            )
+        } else {
+            format!(
+                "{}{}{}-{}{}{}",
+                pick(LETTERS),
+                pick(LETTERS),
+                pick(DIGITS),
+                pick(LETTERS),
+                pick(DIGITS),
+                pick(LETTERS)
+            )
+        };
+        if taken.insert(name.clone()) {
+            return name;
+        }
+    }
+}
+
+/// The closest pair of systems with one in `a` and one in `b`.
+fn closest_pair(systems: &[System], a: &[usize], b: &[usize]) -> (usize, usize) {
+    let position = |i: usize| {
+        systems[i]
+            .position
+            .expect("synthetic systems have positions")
+    };
+    let mut best = (a[0], b[0], f64::INFINITY);
+    for &i in a {
+        for &j in b {
+            let d = position(i).distance(position(j));
+            if d < best.2 {
+                best = (i, j, d);
+            }
+        }
+    }
+    (best.0, best.1)
+}
+
+/// Connect clusters, given as `(center, members)`, into a tree by linking each one to its
+/// nearest predecessor, with an occasional second link to the next nearest. Each link is a gate
+/// between the closest pair of systems in the two clusters.
+fn link_clusters(
+    rng: &mut ChaCha8Rng,
+    systems: &[System],
+    clusters: &[(Position, &[usize])],
+    extra_probability: f64,
+    gates: &mut BTreeSet<(usize, usize)>,
+) {
+    for (i, &(center, members)) in clusters.iter().enumerate().skip(1) {
+        let mut earlier: Vec<&(Position, &[usize])> = clusters[..i].iter().collect();
+        earlier.sort_by(|a, b| a.0.distance(center).total_cmp(&b.0.distance(center)));
+        let links = if earlier.len() > 1 && rng.gen_bool(extra_probability) {
+            2
+        } else {
+            1
+        };
+        for &&(_, other) in earlier.iter().take(links) {
+            let (a, b) = closest_pair(systems, members, other);
+            gates.insert((a.min(b), a.max(b)));
+        }
+    }
+}
+
+/// `p` as a chance `gen_bool` accepts, which panics outside 0..=1.
+fn probability(p: f64) -> f64 {
+    if p.is_nan() {
+        0.0
+    } else {
+        p.clamp(0.0, 1.0)
+    }
+}
+
+/// Generate a galaxy as described in the [module documentation](self).
+pub fn generate(config: &GalaxyConfig) -> Starmap {
+    if config.systems == 0 {
+        return Starmap::new(Vec::new(), Vec::new());
+    }
+    let extra_gate_probability = probability(config.extra_gate_probability);
+    let extra_link_probability = probability(config.extra_link_probability);
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+    let regions = config.regions.clamp(1, config.systems);
+    let constellations_total =
+        (regions * config.constellations_per_region.max(1)).clamp(regions, config.systems);
+
+    // Spread the galaxy so density stays roughly constant as it grows.
+    let galaxy_radius = 40.0 * LIGHT_YEAR * (config.systems as f64).cbrt();
+    let region_radius = galaxy_radius / (regions as f64).cbrt();
+    let constellation_radius = region_radius / 3.0;
+    let system_radius = constellation_radius / 2.0;
+    let origin = Position {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    let region_clusters: Vec<Cluster> = (0..regions)
+        .map(|r| Cluster {
+            id: FIRST_REGION_ID + r as i64,
+            name: format!("Synthetic Region {}", r + 1),
+            center: point_near(&mut rng, origin, galaxy_radius),
+            members: Vec::new(),
+        })
+        .collect();
+    let mut constellations: Vec<(usize, Cluster)> = (0..constellations_total)
+        .map(|c| {
+            let region = c % regions;
+            let center = point_near(&mut rng, region_clusters[region].center, region_radius);
+            let cluster = Cluster {
+                id: FIRST_CONSTELLATION_ID + c as i64,
+                name: format!("Synthetic Constellation {}", c + 1),
+                center,
+                members: Vec::new(),
+            };
+            (region, cluster)
+        })
+        .collect();
+
+    // Every constellation gets at least one system; the rest land at random.
+    let mut assignment: Vec<usize> = (0..constellations_total).collect();
+    assignment.extend(
+        (constellations_total..config.systems).map(|_| rng.gen_range(0..constellations_total)),
+    );
+    assignment.shuffle(&mut rng);
+
+    let mut taken = HashSet::new();
+    let mut systems = Vec::with_capacity(config.systems);
+    for (i, &c) in assignment.iter().enumerate() {
+        let (region, constellation) = &mut constellations[c];
+        let position = point_near(&mut rng, constellation.center, system_radius);
+        constellation.members.push(i);
+        systems.push(System {
+            id: FIRST_SYSTEM_ID + i as i64,
+            name: system_name(&mut rng, &mut taken),
+            region_id: Some(region_clusters[*region].id),
+            region: Some(region_clusters[*region].name.clone()),
+            constellation_id: Some(constellation.id),
+            constellation: Some(constellation.name.clone()),
+            position: Some(position),
+        });
+    }
+
+    let mut gates = BTreeSet::new();
+    let position = |i: usize| {
+        systems[i]
+            .position
+            .expect("synthetic systems have positions")
+    };
+    for (_, constellation) in &constellations {
+        let members = &constellation.members;
+        for (k, &i) in members.iter().enumerate().skip(1) {
+            // Nearest earlier member keeps the constellation connected.
+            let nearest = members[..k]
+                .iter()
+                .copied()
+                .min_by(|&a, &b| {
+                    position(a)
+                        .distance(position(i))
+                        .total_cmp(&position(b).distance(position(i)))
+                })
+                .expect("k >= 1");
+            gates.insert((nearest.min(i), nearest.max(i)));
+        }
+        for &i in members {
+            if members.len() > 2 && rng.gen_bool(extra_gate_probability) {
+                let candidate = members
+                    .iter()
+                    .copied()
+                    .filter(|&j| j != i && !gates.contains(&(i.min(j), i.max(j))))
+                    .min_by(|&a, &b| {
+                        position(a)
+                            .distance(position(i))
+                            .total_cmp(&position(b).distance(position(i)))
+                    });
+                if let Some(j) = candidate {
+                    gates.insert((i.min(j), i.max(j)));
+                }
+            }
+        }
+    }
+
+    for region in 0..regions {
+        let in_region: Vec<(Position, &[usize])> = constellations
+            .iter()
+            .filter(|(r, _)| *r == region)
+            .map(|(_, c)| (c.center, c.members.as_slice()))
+            .collect();
+        link_clusters(
+            &mut rng,
+            &systems,
+            &in_region,
+            extra_link_probability,
+            &mut gates,
+        );
+    }
+
+    // Link regions through their outermost constellations rather than comparing every pair
+    // of systems, which would be quadratic in the region size.
+    let border: Vec<(Position, &[usize])> = region_clusters
+        .iter()
+        .enumerate()
+        .map(|(r, region)| {
+            let outermost = constellations
+                .iter()
+                .filter(|(cr, _)| *cr == r)
+                .max_by(|(_, a), (_, b)| {
+                    a.center
+                        .distance(region.center)
+                        .total_cmp(&b.center.distance(region.center))
+                })
+                .map(|(_, c)| c.members.as_slice())
+                .unwrap_or_default();
+            (region.center, outermost)
+        })
+        .collect();
+    link_clusters(
+        &mut rng,
+        &systems,
+        &border,
+        extra_link_probability,
+        &mut gates,
+    );
+
+    let jumps = gates
+        .into_iter()
+        .map(|(a, b)| Jump {
+            from_id: systems[a].id,
+            to_id: systems[b].id,
+        })
+        .collect();
+    Starmap::new(systems, jumps)
+}
+
+/// Write `starmap` into `conn` using `schema`, in one transaction.
+pub fn write_to_connection(
+    conn: &mut Connection,
+    starmap: &Starmap,
+    schema: SyntheticSchema,
+) -> Result<()> {
+    let tx = conn.transaction()?;
+    match schema {
+        SyntheticSchema::Full => {
+            tx.execute_batch(
+                "CREATE TABLE Regions(regionID INTEGER PRIMARY KEY, regionName TEXT NOT NULL);
+                 CREATE TABLE Constellations(constellationID INTEGER PRIMARY KEY,
+                     regionID INTEGER NOT NULL, constellationName TEXT NOT NULL);
+                 CREATE TABLE SolarSystems(solarSystemId INTEGER PRIMARY KEY,
+                     constellationID INTEGER NOT NULL, regionID INTEGER NOT NULL,
+                     name TEXT NOT NULL, centerX REAL, centerY REAL, centerZ REAL);
+                 CREATE TABLE Jumps(fromSystemId INTEGER NOT NULL, toSystemId INTEGER NOT NULL);",
+            )?;
+            let mut region = tx.prepare("INSERT OR IGNORE INTO Regions VALUES (?1, ?2)")?;
+            let mut constellation =
+                tx.prepare("INSERT OR IGNORE INTO Constellations VALUES (?1, ?2, ?3)")?;
+            let mut system =
+                tx.prepare("INSERT INTO SolarSystems VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+            for s in &starmap.systems {
+                region.execute(params![s.region_id, s.region])?;
+                constellation.execute(params![s.constellation_id, s.region_id, s.constellation])?;
+                let p = s.position;
+                system.execute(params![
+                    s.id,
+                    s.constellation_id,
+                    s.region_id,
+                    s.name,
+                    p.map(|p| p.x),
+                    p.map(|p| p.y),
+                    p.map(|p| p.z)
+                ])?;
+            }
+        }
+        SyntheticSchema::Minimal => {
+            tx.execute_batch(
+                "CREATE TABLE SolarSystems(solarSystemId INTEGER PRIMARY KEY, name TEXT NOT NULL);
+                 CREATE TABLE Jumps(fromSystemId INTEGER NOT NULL, toSystemId INTEGER NOT NULL);",
+            )?;
+            let mut system = tx.prepare("INSERT INTO SolarSystems VALUES (?1, ?2)")?;
+            for s in &starmap.systems {
+                system.execute(params![s.id, s.name])?;
+            }
+        }
+    }
+    {
+        let mut jump = tx.prepare("INSERT INTO Jumps VALUES (?1, ?2)")?;
+        for j in &starmap.jumps {
+            jump.execute(params![j.from_id, j.to_id])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Write `starmap` as a new SQLite database at `path`, replacing any file already there.
+pub fn write_sqlite(path: &Path, starmap: &Starmap, schema: SyntheticSchema) -> Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path).context(ErrorKind::Io, || {
+            format!("failed to replace {}", path.display())
+        })?;
+    }
+    let mut conn = Connection::open(path).context(ErrorKind::Database, || {
+        format!("failed to create SQLite database {}", path.display())
+    })?;
+    write_to_connection(&mut conn, starmap, schema)
+}
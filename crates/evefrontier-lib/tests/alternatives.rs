@@ -0,0 +1,191 @@
+//! K shortest loopless routes, checked against brute-force enumeration, and the similarity
+//! filter that keeps alternatives diverse.
+
+mod common;
+
+use common::starmap;
+use evefrontier_lib::{
+    alternative_routes, k_shortest_paths, AlternativesConfig, Shortfall, Starmap,
+};
+use petgraph::graph::NodeIndex;
+use std::collections::HashSet;
+
+/// Every loopless route from `start` to `goal`, found by depth-first search.
+fn all_routes(starmap: &Starmap, start: usize, goal: usize) -> Vec<Vec<usize>> {
+    fn extend(starmap: &Starmap, route: &mut Vec<usize>, goal: usize, out: &mut Vec<Vec<usize>>) {
+        let v = *route.last().unwrap();
+        if v == goal {
+            out.push(route.clone());
+            return;
+        }
+        for w in starmap.graph.neighbors(NodeIndex::new(v)) {
+            if !route.contains(&w.index()) {
+                route.push(w.index());
+                extend(starmap, route, goal, out);
+                route.pop();
+            }
+        }
+    }
+    let mut out = Vec::new();
+    extend(starmap, &mut vec![start], goal, &mut out);
+    out
+}
+
+#[test]
+fn yen_lists_loopless_routes_in_order_of_jumps() {
+    // A 3 x 3 grid, 0 in one corner and 8 in the opposite one, plus a diagonal 4 - 8.
+    let starmap = starmap(
+        9,
+        &[
+            (0, 1),
+            (1, 2),
+            (3, 4),
+            (4, 5),
+            (6, 7),
+            (7, 8),
+            (0, 3),
+            (3, 6),
+            (1, 4),
+            (4, 7),
+            (2, 5),
+            (5, 8),
+            (4, 8),
+        ],
+    );
+    let mut expected: Vec<usize> = all_routes(&starmap, 0, 8)
+        .iter()
+        .map(|r| r.len() - 1)
+        .collect();
+    expected.sort_unstable();
+
+    let routes = k_shortest_paths(&starmap.graph, 0, 8, usize::MAX);
+    let lengths: Vec<usize> = routes.iter().map(|r| r.len() - 1).collect();
+    assert_eq!(lengths, expected);
+    assert_eq!(routes[0], vec![0, 1, 4, 8]);
+    let distinct: HashSet<&Vec<usize>> = routes.iter().collect();
+    assert_eq!(distinct.len(), routes.len());
+    for route in &routes {
+        assert_eq!(route.first(), Some(&0));
+        assert_eq!(route.last(), Some(&8));
+        assert_eq!(route.iter().collect::<HashSet<_>>().len(), route.len());
+        for pair in route.windows(2) {
+            assert!(starmap
+                .graph
+                .contains_edge(NodeIndex::new(pair[0]), NodeIndex::new(pair[1])));
+        }
+    }
+    assert_eq!(k_shortest_paths(&starmap.graph, 0, 8, 3), routes[..3]);
+}
+
+#[test]
+fn near_copies_of_a_route_are_skipped() {
+    // 0 - 1 - 2 - 3 - 9 is shortest; 0 - 1 - 4 - 3 - 9 swaps one system; 0 - 5 - 6 - 7 - 8 - 9
+    // shares nothing with it.
+    let starmap = starmap(
+        10,
+        &[
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 9),
+            (1, 4),
+            (4, 3),
+            (0, 5),
+            (5, 6),
+            (6, 7),
+            (7, 8),
+            (8, 9),
+        ],
+    );
+    let config = AlternativesConfig {
+        count: 3,
+        max_similarity: 0.4,
+        ..AlternativesConfig::default()
+    };
+    let found = alternative_routes(&starmap.graph, 0, 9, &config);
+    assert_eq!(found.shortfall, Some(Shortfall::Exhausted));
+    let found = found.routes;
+    let routes: Vec<&Vec<usize>> = found.iter().map(|a| &a.route).collect();
+    assert_eq!(routes, [&vec![0, 1, 2, 3, 9], &vec![0, 5, 6, 7, 8, 9]]);
+    assert_eq!(found[0].jumps, 4);
+    assert_eq!(found[0].shared, [1, 2, 3]);
+    assert_eq!(found[0].similarity, 1.0);
+    assert_eq!(found[1].jumps, 5);
+    assert!(found[1].shared.is_empty());
+    assert_eq!(found[1].similarity, 0.0);
+
+    // Allowing half the systems in common keeps the near copy: it shares 1 and 3 out of 1 to 4.
+    let config = AlternativesConfig {
+        max_similarity: 0.5,
+        ..config
+    };
+    let found = alternative_routes(&starmap.graph, 0, 9, &config);
+    assert_eq!(found.shortfall, None);
+    let found = found.routes;
+    assert_eq!(found[1].route, [0, 1, 4, 3, 9]);
+    assert_eq!(found[1].shared, [1, 3]);
+    assert_eq!(found[1].similarity, 0.5);
+    assert_eq!(found.len(), 3);
+}
+
+#[test]
+fn unreachable_and_trivial_routes() {
+    let starmap = starmap(3, &[(0, 1)]);
+    let config = AlternativesConfig::default();
+    let none = alternative_routes(&starmap.graph, 0, 2, &config);
+    assert!(none.routes.is_empty());
+    assert_eq!(none.shortfall, Some(Shortfall::Exhausted));
+    assert!(k_shortest_paths(&starmap.graph, 0, 2, 5).is_empty());
+
+    let same = alternative_routes(&starmap.graph, 1, 1, &config).routes;
+    assert_eq!(same.len(), 1);
+    assert_eq!(same[0].route, [1]);
+    assert_eq!(same[0].jumps, 0);
+    assert_eq!(k_shortest_paths(&starmap.graph, 0, 1, 5), vec![vec![0, 1]]);
+}
+
+#[test]
+fn a_spent_budget_is_told_apart_from_running_out_of_routes() {
+    // Three near copies 0 - 1 - {2, 3, 4} - 5 - 6, and a way round 0 - 7 - 8 - 9 - 10 - 6.
+    let starmap = starmap(
+        11,
+        &[
+            (0, 1),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 5),
+            (3, 5),
+            (4, 5),
+            (5, 6),
+            (0, 7),
+            (7, 8),
+            (8, 9),
+            (9, 10),
+            (10, 6),
+        ],
+    );
+    let config = AlternativesConfig {
+        count: 2,
+        max_similarity: 0.4,
+        candidates_per_route: 1,
+    };
+    // Two candidates are both near copies, so the way round is never reached.
+    let found = alternative_routes(&starmap.graph, 0, 6, &config);
+    assert_eq!(found.routes.len(), 1);
+    assert_eq!(found.shortfall, Some(Shortfall::BudgetSpent));
+
+    let config = AlternativesConfig {
+        candidates_per_route: 100,
+        ..config
+    };
+    let found = alternative_routes(&starmap.graph, 0, 6, &config);
+    assert_eq!(found.routes[1].route, [0, 7, 8, 9, 10, 6]);
+    assert_eq!(found.shortfall, None);
+
+    // All four loopless routes are looked at, and only two are different enough.
+    let config = AlternativesConfig { count: 3, ..config };
+    let found = alternative_routes(&starmap.graph, 0, 6, &config);
+    assert_eq!(found.routes.len(), 2);
+    assert_eq!(found.shortfall, Some(Shortfall::Exhausted));
+}
@@ -0,0 +1,163 @@
+//! Pruning the asset cache by count and age, and protecting the asset the DB in use came from.
+#![cfg(feature = "download")]
+
+use evefrontier_lib::cache::{self, record_download};
+use evefrontier_lib::{
+    install_from, installed_asset, list_cache, prune_cache, DatasetSelector, DownloadOptions,
+    PrunePolicy, SilentProgress,
+};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DAY: u64 = 24 * 60 * 60;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("evefrontier-cache-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Cache a release asset called `name`, last used `days_ago`.
+fn cached(root: &Path, name: &str, days_ago: u64) -> PathBuf {
+    let asset = root.join(name);
+    std::fs::write(&asset, name.as_bytes()).unwrap();
+    record_download(&asset, Some("v1"), "https://example.com/asset").unwrap();
+    let mut meta = cache::read_metadata(&asset).unwrap().unwrap();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    meta.last_used = Some(now.as_secs() - days_ago * DAY);
+    cache::write_metadata(&asset, &meta).unwrap();
+    asset
+}
+
+fn names(root: &Path) -> Vec<String> {
+    list_cache(root)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.name)
+        .collect()
+}
+
+fn removed_names(removed: &[evefrontier_lib::CacheEntry]) -> Vec<&str> {
+    removed.iter().map(|e| e.name.as_str()).collect()
+}
+
+#[test]
+fn prune_keeps_the_most_recently_used_assets() {
+    let root = temp_dir("keep");
+    cached(&root, "a.db", 1);
+    cached(&root, "b.db", 3);
+    cached(&root, "c.db", 2);
+
+    let policy = PrunePolicy {
+        keep: Some(2),
+        ..PrunePolicy::default()
+    };
+    let removed = prune_cache(&root, &policy, &[]).unwrap();
+    assert_eq!(removed_names(&removed), ["b.db"]);
+    assert_eq!(names(&root), ["a.db", "c.db"]);
+    assert!(cache::read_metadata(&root.join("b.db")).unwrap().is_none());
+
+    // An empty policy removes nothing; keep: 0 removes everything.
+    assert!(prune_cache(&root, &PrunePolicy::default(), &[])
+        .unwrap()
+        .is_empty());
+    let policy = PrunePolicy {
+        keep: Some(0),
+        ..PrunePolicy::default()
+    };
+    assert_eq!(prune_cache(&root, &policy, &[]).unwrap().len(), 2);
+    assert!(names(&root).is_empty());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn prune_removes_assets_unused_for_longer_than_the_age() {
+    let root = temp_dir("age");
+    cached(&root, "fresh.db", 1);
+    cached(&root, "stale.db", 10);
+    cached(&root, "ancient.db", 40);
+
+    let policy = PrunePolicy {
+        older_than: Some(Duration::from_secs(7 * DAY)),
+        ..PrunePolicy::default()
+    };
+    let removed = prune_cache(&root, &policy, &[]).unwrap();
+    assert_eq!(removed_names(&removed), ["stale.db", "ancient.db"]);
+    assert_eq!(names(&root), ["fresh.db"]);
+
+    // Either condition is enough when both are given.
+    cached(&root, "recent.db", 2);
+    let policy = PrunePolicy {
+        older_than: Some(Duration::from_secs(30 * DAY)),
+        keep: Some(1),
+    };
+    let removed = prune_cache(&root, &policy, &[]).unwrap();
+    assert_eq!(removed_names(&removed), ["recent.db"]);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn the_asset_behind_the_installed_db_is_protected() {
+    let dir = temp_dir("protected");
+    let root = dir.join("cache");
+    std::fs::create_dir_all(&root).unwrap();
+    cached(&root, "old-release.db", 30);
+
+    let fixture =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../docs/fixtures/minimal_static_data.db");
+    let target = dir.join("data").join("static_data.db");
+    let options = DownloadOptions {
+        cache_root: Some(root.clone()),
+        ..DownloadOptions::default()
+    };
+    install_from(
+        fixture.to_str().unwrap(),
+        &target,
+        &DatasetSelector::default(),
+        &options,
+        &SilentProgress,
+    )
+    .unwrap();
+
+    let in_use = installed_asset(&root, &target).unwrap().unwrap();
+    assert_eq!(in_use, root.join("local").join("minimal_static_data.db"));
+    assert_eq!(
+        installed_asset(&root, &dir.join("nothing.db")).unwrap(),
+        None
+    );
+
+    let policy = PrunePolicy {
+        keep: Some(0),
+        ..PrunePolicy::default()
+    };
+    // The installed copy lives outside the cache, so protecting it alone keeps nothing.
+    let removed = prune_cache(&root, &policy, &[&target]).unwrap();
+    assert_eq!(
+        removed_names(&removed),
+        ["local/minimal_static_data.db", "old-release.db"]
+    );
+    assert_eq!(installed_asset(&root, &target).unwrap(), None);
+
+    install_from(
+        fixture.to_str().unwrap(),
+        &target,
+        &DatasetSelector::default(),
+        &options,
+        &SilentProgress,
+    )
+    .unwrap();
+    cached(&root, "old-release.db", 30);
+    let in_use = installed_asset(&root, &target).unwrap().unwrap();
+    let removed = prune_cache(&root, &policy, &[&target, &in_use]).unwrap();
+    assert_eq!(removed_names(&removed), ["old-release.db"]);
+    assert_eq!(names(&root), ["local/minimal_static_data.db"]);
+    assert!(evefrontier_lib::clear_cache(&root, &[&in_use])
+        .unwrap()
+        .is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,126 @@
+//! Degree, betweenness and closeness centrality on small graphs with known values, and sampling.
+
+mod common;
+
+use common::starmap;
+use evefrontier_lib::centrality::rank_by;
+use evefrontier_lib::{
+    compute_centrality, Centrality, CentralityConfig, CentralityMetric, ErrorKind, Starmap,
+};
+
+fn exact(starmap: &Starmap) -> Vec<Centrality> {
+    compute_centrality(&starmap.graph, &CentralityConfig::default()).unwrap()
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn path_and_cycle_match_hand_computed_values() {
+    // 0 - 1 - 2 - 3 - 4
+    let path = exact(&starmap(5, &[(0, 1), (1, 2), (2, 3), (3, 4)]));
+    let degrees: Vec<usize> = path.iter().map(|c| c.degree).collect();
+    assert_eq!(degrees, [1, 2, 2, 2, 1]);
+    // System 2 lies on the routes between {0, 1} and {3, 4}: 4 of the 6 pairs without it.
+    assert_close(path[2].betweenness, 4.0 / 6.0);
+    assert_close(path[1].betweenness, 3.0 / 6.0);
+    assert_close(path[0].betweenness, 0.0);
+    assert_close(path[2].closeness, 4.0 / 6.0);
+    assert_close(path[0].closeness, 4.0 / 10.0);
+
+    // In a square each opposite pair has two routes, so each corner carries half of one pair.
+    let square = exact(&starmap(4, &[(0, 1), (1, 2), (2, 3), (3, 0)]));
+    for c in &square {
+        assert_close(c.betweenness, 0.5 / 3.0);
+        assert_close(c.closeness, 3.0 / 4.0);
+    }
+}
+
+#[test]
+fn closeness_is_scaled_by_component_size() {
+    // A chain of three and a separate pair: both middle-of-component systems are one jump from
+    // everything they can reach, but the pair covers less of the map.
+    let scores = exact(&starmap(5, &[(0, 1), (1, 2), (3, 4)]));
+    assert_close(scores[1].closeness, 2.0 / 4.0);
+    assert_close(scores[3].closeness, 1.0 / 4.0);
+    assert_close(scores[1].betweenness, 1.0 / 6.0);
+
+    let ranked = rank_by(&scores, CentralityMetric::Closeness);
+    let order: Vec<usize> = ranked.iter().map(|c| c.system).collect();
+    assert_eq!(order, [1, 0, 2, 3, 4]);
+}
+
+/// A `side` by `side` grid of systems.
+fn grid(side: i64) -> Starmap {
+    let mut jumps = Vec::new();
+    for r in 0..side {
+        for c in 0..side {
+            let id = r * side + c;
+            if c + 1 < side {
+                jumps.push((id, id + 1));
+            }
+            if r + 1 < side {
+                jumps.push((id, id + side));
+            }
+        }
+    }
+    starmap(side * side, &jumps)
+}
+
+#[test]
+fn sampling_is_seeded_and_close_to_exact() {
+    let starmap = grid(15);
+    let exact = exact(&starmap);
+    let sampled = |seed| {
+        compute_centrality(
+            &starmap.graph,
+            &CentralityConfig {
+                samples: Some(120),
+                seed,
+            },
+        )
+        .unwrap()
+    };
+    assert_eq!(sampled(7), sampled(7));
+    assert_ne!(sampled(7), sampled(8));
+
+    let centre = 7 * 15 + 7;
+    for seed in [1, 2, 3] {
+        let estimate = sampled(seed);
+        let error = |f: fn(&Centrality) -> f64| (f(&estimate[centre]) - f(&exact[centre])).abs();
+        assert!(error(|c| c.betweenness) < 0.25 * exact[centre].betweenness);
+        assert!(error(|c| c.closeness) < 0.1 * exact[centre].closeness);
+        assert_eq!(estimate[centre].degree, 4);
+    }
+
+    // Asking for at least as many samples as systems is exact.
+    let all = compute_centrality(
+        &starmap.graph,
+        &CentralityConfig {
+            samples: Some(10_000),
+            seed: 3,
+        },
+    )
+    .unwrap();
+    assert_eq!(all, exact);
+
+    let err = compute_centrality(
+        &starmap.graph,
+        &CentralityConfig {
+            samples: Some(0),
+            seed: 3,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(
+        err.to_string(),
+        "centrality needs at least one sampled system"
+    );
+}
@@ -0,0 +1,104 @@
+//! Articulation points and bridges, checked by hand and against brute-force removal.
+
+mod common;
+
+use common::starmap;
+use evefrontier_lib::{find_chokepoints, Starmap};
+
+/// Sizes of the components left when the `removed` system or the `cut` gate is taken out.
+fn pieces(starmap: &Starmap, removed: Option<usize>, cut: Option<(usize, usize)>) -> Vec<usize> {
+    let n = starmap.systems.len();
+    let mut adjacency = vec![Vec::new(); n];
+    for j in &starmap.jumps {
+        let (a, b) = (j.from_id as usize, j.to_id as usize);
+        if Some(a) == removed || Some(b) == removed || cut == Some((a.min(b), a.max(b))) {
+            continue;
+        }
+        adjacency[a].push(b);
+        adjacency[b].push(a);
+    }
+    let mut seen = vec![false; n];
+    if let Some(r) = removed {
+        seen[r] = true;
+    }
+    let mut sizes = Vec::new();
+    for start in 0..n {
+        if seen[start] {
+            continue;
+        }
+        let mut stack = vec![start];
+        seen[start] = true;
+        let mut size = 0;
+        while let Some(v) = stack.pop() {
+            size += 1;
+            for &w in &adjacency[v] {
+                if !seen[w] {
+                    seen[w] = true;
+                    stack.push(w);
+                }
+            }
+        }
+        sizes.push(size);
+    }
+    sizes
+}
+
+#[test]
+fn finds_the_pocket_behind_a_single_gate() {
+    // A triangle 0-1-2 with a tail 2-3-4.
+    let map = starmap(5, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4)]);
+    let chokepoints = find_chokepoints(&map.graph);
+
+    let points: Vec<(usize, usize)> = chokepoints
+        .articulation_points
+        .iter()
+        .map(|p| (p.system, p.cut_off))
+        .collect();
+    assert_eq!(points, vec![(2, 2), (3, 1)]);
+    let bridges: Vec<(usize, usize, usize)> = chokepoints
+        .bridges
+        .iter()
+        .map(|b| (b.a, b.b, b.cut_off))
+        .collect();
+    assert_eq!(bridges, vec![(2, 3, 2), (3, 4, 1)]);
+}
+
+#[test]
+fn agrees_with_brute_force_removal() {
+    // A deterministic tangle of cycles, trees and a separate component.
+    let mut jumps = Vec::new();
+    let mut state = 17u64;
+    for v in 1..60i64 {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        jumps.push((v, (state >> 33) as i64 % v));
+        if v % 4 == 0 {
+            jumps.push((v, (state >> 40) as i64 % v));
+        }
+    }
+    jumps.extend([(60, 61), (61, 62)]);
+    let map = starmap(63, &jumps);
+    let baseline = pieces(&map, None, None).len();
+    let chokepoints = find_chokepoints(&map.graph);
+
+    for v in 0..63 {
+        let after = pieces(&map, Some(v), None);
+        let found = chokepoints
+            .articulation_points
+            .iter()
+            .find(|p| p.system == v);
+        let splits = after.len() > baseline;
+        assert_eq!(found.is_some(), splits, "system {}", v);
+        if let Some(point) = found {
+            assert_eq!(point.pieces, after.len() - baseline + 1, "system {}", v);
+        }
+    }
+    for j in &map.jumps {
+        let (a, b) = (j.from_id as usize, j.to_id as usize);
+        let edge = (a.min(b), a.max(b));
+        let after = pieces(&map, None, Some(edge));
+        let found = chokepoints.bridges.iter().any(|x| (x.a, x.b) == edge);
+        assert_eq!(found, after.len() > baseline, "gate {:?}", edge);
+    }
+}
@@ -0,0 +1,56 @@
+//! Small star maps for the integration tests, built from ids, gates and light-year positions.
+// Each test crate uses only some of these.
+#![allow(dead_code)]
+
+use evefrontier_lib::{Jump, Position, Starmap, System, LIGHT_YEAR};
+
+/// A system named `S<id>` with no region, constellation or position.
+pub fn system(id: i64) -> System {
+    System {
+        id,
+        name: format!("S{}", id),
+        region_id: None,
+        region: None,
+        constellation_id: None,
+        constellation: None,
+        position: None,
+    }
+}
+
+/// [`system`] placed `x` light-years along the x axis.
+pub fn system_at(id: i64, x: f64) -> System {
+    System {
+        position: Some(Position {
+            x: x * LIGHT_YEAR,
+            y: 0.0,
+            z: 0.0,
+        }),
+        ..system(id)
+    }
+}
+
+pub fn jumps(gates: &[(i64, i64)]) -> Vec<Jump> {
+    gates
+        .iter()
+        .map(|&(from_id, to_id)| Jump { from_id, to_id })
+        .collect()
+}
+
+/// Systems `S0` to `S<count - 1>`, with ids equal to their index, linked by `gates`.
+pub fn starmap(count: i64, gates: &[(i64, i64)]) -> Starmap {
+    Starmap::new((0..count).map(system).collect(), jumps(gates))
+}
+
+/// Systems `S0`, `S1`, ... on the x axis at the given light-years, with ids equal to their
+/// index, linked by `gates`. `None` leaves a system without coordinates.
+pub fn line(xs: &[Option<f64>], gates: &[(i64, i64)]) -> Starmap {
+    let systems = xs
+        .iter()
+        .zip(0..)
+        .map(|(x, id)| match x {
+            Some(x) => system_at(id, *x),
+            None => system(id),
+        })
+        .collect();
+    Starmap::new(systems, jumps(gates))
+}
@@ -0,0 +1,56 @@
+//! Component analysis over a star map split into several gate networks.
+
+use evefrontier_lib::components::{summarize_components, tour_components, unreachable_regions};
+use evefrontier_lib::rusqlite::Connection;
+use evefrontier_lib::{connected_components, unreachable_from, SilentProgress, Starmap};
+
+fn split_starmap() -> Starmap {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE Regions(regionID INTEGER, regionName TEXT);
+         CREATE TABLE SolarSystems(solarSystemId INTEGER PRIMARY KEY, name TEXT, regionID INTEGER);
+         CREATE TABLE Jumps(fromSystemId INTEGER, toSystemId INTEGER);
+         INSERT INTO Regions VALUES (1, 'Core'), (2, 'Rim'), (3, 'Void');
+         INSERT INTO SolarSystems VALUES (1, 'A', 1), (2, 'B', 1), (3, 'C', 2), (4, 'D', 2),
+             (5, 'E', 2), (6, 'F', 3);
+         INSERT INTO Jumps VALUES (1, 2), (2, 3), (4, 5);",
+    )
+    .unwrap();
+    Starmap::from_connection(&conn).unwrap()
+}
+
+#[test]
+fn components_are_ordered_largest_first() {
+    let starmap = split_starmap();
+    assert_eq!(
+        connected_components(&starmap.graph),
+        vec![vec![0, 1, 2], vec![3, 4], vec![5]]
+    );
+    let summary = summarize_components(&starmap);
+    assert_eq!(summary[0].regions, vec!["Core", "Rim"]);
+    assert_eq!(summary[2].systems, vec!["F"]);
+}
+
+#[test]
+fn reports_unreachable_systems_by_region() {
+    let starmap = split_starmap();
+    let unreachable = unreachable_from(&starmap.graph, 0);
+    assert_eq!(unreachable, vec![3, 4, 5]);
+
+    let regions = unreachable_regions(&starmap.systems, &unreachable);
+    assert_eq!(regions.len(), 2);
+    assert_eq!(regions[0].region.as_deref(), Some("Rim"));
+    assert_eq!((regions[0].unreachable, regions[0].total), (2, 3));
+    assert!(!regions[0].is_entirely_unreachable());
+    assert!(regions[1].is_entirely_unreachable());
+}
+
+#[test]
+fn tours_every_component_starting_with_the_start() {
+    let starmap = split_starmap();
+    let tours = tour_components(&starmap.graph, 4, &SilentProgress);
+    assert_eq!(tours.len(), 3);
+    assert_eq!(tours[0].first(), Some(&4));
+    assert_eq!(tours[1].first(), Some(&0));
+    assert_eq!(tours[2], vec![5]);
+}
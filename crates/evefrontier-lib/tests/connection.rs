@@ -0,0 +1,46 @@
+//! Loading through a caller-owned connection, and opening dataset files without side effects.
+
+use evefrontier_lib::rusqlite::Connection;
+use evefrontier_lib::{ErrorKind, Starmap};
+use std::path::PathBuf;
+
+fn in_memory_starmap() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE SolarSystems(solarSystemId INTEGER PRIMARY KEY, name TEXT NOT NULL);
+         CREATE TABLE Jumps(fromSystemId INTEGER NOT NULL, toSystemId INTEGER NOT NULL);
+         INSERT INTO SolarSystems VALUES (1, 'Alpha'), (2, 'Beta'), (3, 'Gamma');
+         INSERT INTO Jumps VALUES (1, 2), (2, 3);",
+    )
+    .unwrap();
+    conn
+}
+
+#[test]
+fn loads_from_an_in_memory_connection() {
+    let conn = in_memory_starmap();
+    let starmap = Starmap::from_connection(&conn).unwrap();
+    assert_eq!(starmap.systems.len(), 3);
+    assert_eq!(starmap.graph.edge_count(), 2);
+
+    let route =
+        evefrontier_lib::route_between(&starmap.systems, &starmap.graph, "Alpha", "Gamma").unwrap();
+    assert_eq!(route.len(), 3);
+}
+
+#[test]
+fn in_memory_schema_errors_name_the_database() {
+    let conn = Connection::open_in_memory().unwrap();
+    let err = evefrontier_lib::load_starmap_from_connection(&conn).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Schema);
+    assert!(err.to_string().contains("in-memory database"));
+}
+
+#[test]
+fn missing_dataset_is_not_created() {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("evefrontier-missing-{}.db", std::process::id()));
+    let err = evefrontier_lib::load_starmap(&path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Io);
+    assert!(!path.exists());
+}
@@ -0,0 +1,176 @@
+//! Differences between two star maps: systems matched by id, jumps in either direction and
+//! region/constellation membership.
+
+mod common;
+
+use evefrontier_lib::diff::{JumpRef, Membership, RenamedSystem, SystemRef};
+use evefrontier_lib::{diff_starmaps, Starmap, System};
+
+fn system(id: i64, name: &str, region: (i64, &str), constellation: (i64, &str)) -> System {
+    System {
+        name: name.to_string(),
+        region_id: Some(region.0),
+        region: Some(region.1.to_string()),
+        constellation_id: Some(constellation.0),
+        constellation: Some(constellation.1.to_string()),
+        ..common::system(id)
+    }
+}
+
+fn system_ref(id: i64, name: &str) -> SystemRef {
+    SystemRef {
+        id,
+        name: name.to_string(),
+    }
+}
+
+fn jump_ref(from: (i64, &str), to: (i64, &str)) -> JumpRef {
+    JumpRef {
+        from: system_ref(from.0, from.1),
+        to: system_ref(to.0, to.1),
+    }
+}
+
+fn membership(region: (i64, &str), constellation: (i64, &str)) -> Membership {
+    Membership {
+        region_id: Some(region.0),
+        region: Some(region.1.to_string()),
+        constellation_id: Some(constellation.0),
+        constellation: Some(constellation.1.to_string()),
+    }
+}
+
+const CORE: (i64, &str) = (10, "Core");
+const RIM: (i64, &str) = (20, "Rim");
+const INNER: (i64, &str) = (100, "Inner");
+const OUTER: (i64, &str) = (200, "Outer");
+
+fn old_map() -> Starmap {
+    Starmap::new(
+        vec![
+            system(1, "Alpha", CORE, INNER),
+            system(2, "Beta", CORE, INNER),
+            system(3, "Gamma", CORE, INNER),
+            system(4, "Delta", RIM, OUTER),
+        ],
+        common::jumps(&[(1, 2), (2, 3), (3, 4)]),
+    )
+}
+
+#[test]
+fn identical_maps_have_an_empty_diff() {
+    assert!(diff_starmaps(&old_map(), &old_map()).is_empty());
+
+    // Jump direction and duplicates do not count as changes.
+    let mut reversed = old_map();
+    reversed.jumps = common::jumps(&[(2, 1), (3, 2), (4, 3), (1, 2)]);
+    assert!(diff_starmaps(&old_map(), &reversed).is_empty());
+}
+
+#[test]
+fn systems_are_matched_by_id() {
+    let new = Starmap::new(
+        vec![
+            // Renamed, same id.
+            system(1, "Alpha Prime", CORE, INNER),
+            system(2, "Beta", CORE, INNER),
+            // Moved to another constellation, and to another region and constellation.
+            system(3, "Gamma", CORE, OUTER),
+            system(4, "Delta", CORE, INNER),
+            // Added; 6 is gone.
+            system(5, "Epsilon", RIM, OUTER),
+        ],
+        common::jumps(&[(1, 2), (2, 3), (3, 4)]),
+    );
+    let mut old = old_map();
+    old.systems.push(system(6, "Zeta", RIM, OUTER));
+    let old = Starmap::new(old.systems, old.jumps);
+
+    let diff = diff_starmaps(&old, &new);
+    assert_eq!(diff.added_systems, [system_ref(5, "Epsilon")]);
+    assert_eq!(diff.removed_systems, [system_ref(6, "Zeta")]);
+    assert_eq!(
+        diff.renamed_systems,
+        [RenamedSystem {
+            id: 1,
+            old_name: "Alpha".to_string(),
+            new_name: "Alpha Prime".to_string(),
+        }]
+    );
+    let changes: Vec<_> = diff
+        .membership_changes
+        .iter()
+        .map(|c| (c.system.clone(), c.old.clone(), c.new.clone()))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            (
+                system_ref(3, "Gamma"),
+                membership(CORE, INNER),
+                membership(CORE, OUTER)
+            ),
+            (
+                system_ref(4, "Delta"),
+                membership(RIM, OUTER),
+                membership(CORE, INNER)
+            ),
+        ]
+    );
+    assert!(diff.added_jumps.is_empty());
+    assert!(diff.removed_jumps.is_empty());
+    assert!(!diff.is_empty());
+}
+
+#[test]
+fn jumps_are_compared_without_direction() {
+    let old = old_map();
+    let mut new = old_map();
+    new.systems.push(system(5, "Epsilon", RIM, OUTER));
+    // 2 - 3 is gone, 1 - 4 and 5 - 4 are new, and 3 - 4 is listed the other way round.
+    new.jumps = common::jumps(&[(2, 1), (4, 3), (4, 1), (5, 4)]);
+    let new = Starmap::new(new.systems, new.jumps);
+
+    let diff = diff_starmaps(&old, &new);
+    assert_eq!(
+        diff.added_jumps,
+        [
+            jump_ref((1, "Alpha"), (4, "Delta")),
+            jump_ref((4, "Delta"), (5, "Epsilon")),
+        ]
+    );
+    assert_eq!(diff.removed_jumps, [jump_ref((2, "Beta"), (3, "Gamma"))]);
+    assert_eq!(diff.added_systems, [system_ref(5, "Epsilon")]);
+
+    // The same change seen the other way round.
+    let back = diff_starmaps(&new, &old);
+    assert_eq!(back.added_jumps, diff.removed_jumps);
+    assert_eq!(back.removed_jumps, diff.added_jumps);
+    assert_eq!(back.removed_systems, [system_ref(5, "Epsilon")]);
+}
+
+#[test]
+fn removed_jumps_are_named_as_in_the_old_map() {
+    let old = old_map();
+    let new = Starmap::new(
+        vec![
+            system(1, "Alpha", CORE, INNER),
+            system(2, "Beta Renamed", CORE, INNER),
+        ],
+        common::jumps(&[(1, 2)]),
+    );
+
+    let diff = diff_starmaps(&old, &new);
+    assert_eq!(
+        diff.removed_jumps,
+        [
+            jump_ref((2, "Beta"), (3, "Gamma")),
+            jump_ref((3, "Gamma"), (4, "Delta")),
+        ]
+    );
+    assert_eq!(
+        diff.removed_systems,
+        [system_ref(3, "Gamma"), system_ref(4, "Delta")]
+    );
+    assert_eq!(diff.renamed_systems.len(), 1);
+}
@@ -0,0 +1,318 @@
+//! Exercises the blocking and async downloaders against a local stand-in for the GitHub
+//! releases API and asset host.
+#![cfg(feature = "download")]
+
+use evefrontier_lib::cache::{self, AssetMetadata};
+use evefrontier_lib::{
+    ensure_dataset_with, DatasetSelector, DownloadOptions, ErrorKind, NamePattern, SilentProgress,
+};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+const ASSET_NAME: &str = "static_data.db.gz";
+
+fn fixture_db() -> Vec<u8> {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../docs/fixtures/minimal_static_data.db");
+    std::fs::read(path).expect("read fixture DB")
+}
+
+/// Serves `GET /releases/latest` with a single gzipped-DB asset and `GET /assets/<name>` with
+/// its bytes. Counts asset downloads so tests can check the cache is reused.
+struct StandInServer {
+    base: String,
+    asset_requests: Arc<AtomicUsize>,
+}
+
+impl StandInServer {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind stand-in server");
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let asset_requests = Arc::new(AtomicUsize::new(0));
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(&fixture_db()).unwrap();
+        let asset = encoder.finish().unwrap();
+        let release = format!(
+            r#"{{"tag_name":"v-test","assets":[{{"name":"{name}","size":{size},"browser_download_url":"{base}/assets/{name}"}}]}}"#,
+            name = ASSET_NAME,
+            size = asset.len(),
+            base = base,
+        );
+
+        let counter = asset_requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                serve(stream, &release, &asset, &counter);
+            }
+        });
+        StandInServer {
+            base,
+            asset_requests,
+        }
+    }
+
+    fn options(&self, cache_root: &Path) -> DownloadOptions {
+        DownloadOptions {
+            release_api: format!("{}/releases/latest", self.base),
+            cache_root: Some(cache_root.to_path_buf()),
+            ..DownloadOptions::default()
+        }
+    }
+
+    fn asset_downloads(&self) -> usize {
+        self.asset_requests.load(Ordering::SeqCst)
+    }
+}
+
+fn serve(stream: TcpStream, release: &str, asset: &[u8], counter: &AtomicUsize) {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if header == "\r\n" => break,
+            Ok(_) => {}
+        }
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let (status, content_type, body): (&str, &str, &[u8]) = if path == "/releases/latest" {
+        ("200 OK", "application/json", release.as_bytes())
+    } else if path == format!("/assets/{}", ASSET_NAME) {
+        counter.fetch_add(1, Ordering::SeqCst);
+        ("200 OK", "application/octet-stream", asset)
+    } else {
+        ("404 Not Found", "text/plain", b"not found")
+    };
+
+    let mut stream = reader.into_inner();
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(body);
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "evefrontier-download-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn assert_installed(db: &Path) {
+    let (systems, jumps) = evefrontier_lib::load_starmap(db).expect("installed DB loads");
+    assert_eq!(systems.len(), 3);
+    assert_eq!(jumps.len(), 3);
+}
+
+#[test]
+fn blocking_download_installs_and_reuses_cache() {
+    let server = StandInServer::start();
+    let dir = temp_dir("blocking");
+    let options = server.options(&dir.join("cache"));
+    let target = dir.join("data").join("static_data.db");
+
+    let db = ensure_dataset_with(
+        Some(&target),
+        &DatasetSelector::default(),
+        &options,
+        &SilentProgress,
+    )
+    .unwrap();
+    assert_eq!(db, target);
+    assert_installed(&target);
+    assert!(dir.join("cache").join(ASSET_NAME).exists());
+    assert!(dir.join("data").join("static_data.db.meta.json").exists());
+    assert_eq!(server.asset_downloads(), 1);
+
+    // A second install comes from the cached asset.
+    std::fs::remove_file(&target).unwrap();
+    ensure_dataset_with(
+        Some(&target),
+        &DatasetSelector::default(),
+        &options,
+        &SilentProgress,
+    )
+    .unwrap();
+    assert_installed(&target);
+    assert_eq!(server.asset_downloads(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn selectors_and_force_decide_whether_an_installed_db_is_kept() {
+    let server = StandInServer::start();
+    let dir = temp_dir("selector");
+    let options = server.options(&dir.join("cache"));
+    let target = dir.join("data").join("static_data.db");
+    let install = |selector: &DatasetSelector, options: &DownloadOptions| {
+        ensure_dataset_with(Some(&target), selector, options, &SilentProgress).unwrap();
+        cache::read_metadata(&target).unwrap().unwrap()
+    };
+
+    let meta = install(&DatasetSelector::default(), &options);
+    assert_eq!(meta.asset.as_deref(), Some(ASSET_NAME));
+    // Pretend the installed DB came from another asset of the release.
+    let older = AssetMetadata {
+        asset: Some("older.db.gz".into()),
+        ..meta
+    };
+    cache::write_metadata(&target, &older).unwrap();
+
+    // Without a pattern any installed DB will do.
+    let meta = install(&DatasetSelector::default(), &options);
+    assert_eq!(meta.asset.as_deref(), Some("older.db.gz"));
+
+    // An asset pattern must match the asset the DB came from.
+    let selector = DatasetSelector {
+        asset: Some(NamePattern::glob("static_data*").unwrap()),
+        ..DatasetSelector::default()
+    };
+    let meta = install(&selector, &options);
+    assert_eq!(meta.asset.as_deref(), Some(ASSET_NAME));
+    assert_eq!(server.asset_downloads(), 1);
+
+    // Reinstalling from the cache is skipped once the DB matches.
+    std::fs::write(&target, b"left alone").unwrap();
+    install(&selector, &options);
+    assert_eq!(std::fs::read(&target).unwrap(), b"left alone");
+
+    // --force fetches the asset again and replaces the DB.
+    let forced = DownloadOptions {
+        force: true,
+        ..server.options(&dir.join("cache"))
+    };
+    install(&selector, &forced);
+    assert_installed(&target);
+    assert_eq!(server.asset_downloads(), 2);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn concurrent_downloads_share_one_asset_fetch() {
+    let server = Arc::new(StandInServer::start());
+    let dir = temp_dir("concurrent");
+    let cache_root = dir.join("cache");
+
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let options = server.options(&cache_root);
+            let target = dir.join(format!("data-{}", i)).join("static_data.db");
+            thread::spawn(move || {
+                ensure_dataset_with(
+                    Some(&target),
+                    &DatasetSelector::default(),
+                    &options,
+                    &SilentProgress,
+                )
+                .map(|_| target)
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_installed(&handle.join().unwrap().unwrap());
+    }
+    assert_eq!(server.asset_downloads(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn release_api_errors_are_download_errors() {
+    let server = StandInServer::start();
+    let dir = temp_dir("api-error");
+    let options = DownloadOptions {
+        release_api: format!("{}/missing", server.base),
+        cache_root: Some(dir.join("cache")),
+        ..DownloadOptions::default()
+    };
+
+    let err = ensure_dataset_with(None, &DatasetSelector::default(), &options, &SilentProgress)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Download);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test(flavor = "multi_thread")]
+async fn async_download_shares_cache_with_blocking_path() {
+    let server = StandInServer::start();
+    let dir = temp_dir("async");
+    let options = server.options(&dir.join("cache"));
+    let target = dir.join("data").join("static_data.db");
+
+    let db = evefrontier_lib::nonblocking::ensure_dataset_with(
+        Some(&target),
+        &DatasetSelector::default(),
+        &options,
+        Arc::new(SilentProgress),
+    )
+    .await
+    .unwrap();
+    assert_eq!(db, target);
+    assert_installed(&target);
+    assert_eq!(server.asset_downloads(), 1);
+
+    // The blocking downloader picks up the asset the async one cached.
+    let other = dir.join("other").join("static_data.db");
+    let blocking_options = options.clone();
+    let blocking_target = other.clone();
+    tokio::task::spawn_blocking(move || {
+        ensure_dataset_with(
+            Some(&blocking_target),
+            &DatasetSelector::default(),
+            &blocking_options,
+            &SilentProgress,
+        )
+    })
+    .await
+    .unwrap()
+    .unwrap();
+    assert_installed(&other);
+    assert_eq!(server.asset_downloads(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn async_download_runs_on_a_current_thread_runtime() {
+    let server = StandInServer::start();
+    let dir = temp_dir("async-current-thread");
+    let options = server.options(&dir.join("cache"));
+
+    let db = evefrontier_lib::nonblocking::ensure_dataset_with(
+        None,
+        &DatasetSelector::default(),
+        &options,
+        Arc::new(SilentProgress),
+    )
+    .await
+    .unwrap();
+    assert!(db.starts_with(dir.join("cache")));
+    assert_installed(&db);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,159 @@
+//! Exact DOT, GraphML and GEXF output, escaping of awkward names, and the region and
+//! neighbourhood filters that pick what to export.
+
+mod common;
+
+use evefrontier_lib::export::{neighbourhood, region_systems};
+use evefrontier_lib::{export_starmap, ErrorKind, ExportFormat, Position, Starmap, System};
+
+/// System 1 has a name and region that need escaping and coordinates; system 2 has only a name.
+fn starmap() -> Starmap {
+    let awkward = System {
+        name: r#"A&B "x""#.to_string(),
+        region_id: Some(10),
+        region: Some("R<1>".to_string()),
+        position: Some(Position {
+            x: 1.5,
+            y: -2.0,
+            z: 0.0,
+        }),
+        ..common::system(1)
+    };
+    Starmap::new(vec![awkward, common::system(2)], common::jumps(&[(1, 2)]))
+}
+
+fn export(starmap: &Starmap, nodes: Option<&[usize]>, format: ExportFormat) -> String {
+    let mut out = Vec::new();
+    export_starmap(&mut out, starmap, nodes, format).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+const DOT: &str = r#"graph starmap {
+  "1" [label="A&B \"x\"", name="A&B \"x\"", region_id="10", region="R<1>", x="1.5", y="-2", z="0"];
+  "2" [label="S2", name="S2"];
+  "1" -- "2";
+}
+"#;
+
+const GRAPHML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="name" for="node" attr.name="name" attr.type="string"/>
+  <key id="region_id" for="node" attr.name="region_id" attr.type="long"/>
+  <key id="region" for="node" attr.name="region" attr.type="string"/>
+  <key id="constellation_id" for="node" attr.name="constellation_id" attr.type="long"/>
+  <key id="constellation" for="node" attr.name="constellation" attr.type="string"/>
+  <key id="x" for="node" attr.name="x" attr.type="double"/>
+  <key id="y" for="node" attr.name="y" attr.type="double"/>
+  <key id="z" for="node" attr.name="z" attr.type="double"/>
+  <graph id="starmap" edgedefault="undirected">
+    <node id="1">
+      <data key="name">A&amp;B &quot;x&quot;</data>
+      <data key="region_id">10</data>
+      <data key="region">R&lt;1&gt;</data>
+      <data key="x">1.5</data>
+      <data key="y">-2</data>
+      <data key="z">0</data>
+    </node>
+    <node id="2">
+      <data key="name">S2</data>
+    </node>
+    <edge source="1" target="2"/>
+  </graph>
+</graphml>
+"#;
+
+const GEXF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">
+  <graph mode="static" defaultedgetype="undirected">
+    <attributes class="node">
+      <attribute id="region_id" title="region_id" type="long"/>
+      <attribute id="region" title="region" type="string"/>
+      <attribute id="constellation_id" title="constellation_id" type="long"/>
+      <attribute id="constellation" title="constellation" type="string"/>
+      <attribute id="x" title="x" type="double"/>
+      <attribute id="y" title="y" type="double"/>
+      <attribute id="z" title="z" type="double"/>
+    </attributes>
+    <nodes>
+      <node id="1" label="A&amp;B &quot;x&quot;">
+        <attvalues>
+          <attvalue for="region_id" value="10"/>
+          <attvalue for="region" value="R&lt;1&gt;"/>
+          <attvalue for="x" value="1.5"/>
+          <attvalue for="y" value="-2"/>
+          <attvalue for="z" value="0"/>
+        </attvalues>
+        <viz:position x="1.5" y="-2" z="0"/>
+      </node>
+      <node id="2" label="S2">
+        <attvalues>
+        </attvalues>
+      </node>
+    </nodes>
+    <edges>
+      <edge id="0" source="1" target="2"/>
+    </edges>
+  </graph>
+</gexf>
+"#;
+
+#[test]
+fn writers_produce_the_expected_documents() {
+    let starmap = starmap();
+    assert_eq!(export(&starmap, None, ExportFormat::Dot), DOT);
+    assert_eq!(export(&starmap, None, ExportFormat::GraphMl), GRAPHML);
+    assert_eq!(export(&starmap, None, ExportFormat::Gexf), GEXF);
+}
+
+#[test]
+fn dot_escapes_backslashes_and_quotes() {
+    let starmap = Starmap::new(
+        vec![System {
+            name: r#"back\slash "quoted""#.to_string(),
+            ..common::system(7)
+        }],
+        Vec::new(),
+    );
+    assert_eq!(
+        export(&starmap, None, ExportFormat::Dot),
+        "graph starmap {\n  \"7\" [label=\"back\\\\slash \\\"quoted\\\"\", \
+         name=\"back\\\\slash \\\"quoted\\\"\"];\n}\n"
+    );
+}
+
+#[test]
+fn a_subset_keeps_only_the_jumps_inside_it() {
+    let starmap = starmap();
+    let dot = export(&starmap, Some(&[1]), ExportFormat::Dot);
+    assert_eq!(
+        dot,
+        "graph starmap {\n  \"2\" [label=\"S2\", name=\"S2\"];\n}\n"
+    );
+    let gexf = export(&starmap, Some(&[0]), ExportFormat::Gexf);
+    assert!(gexf.contains(r#"<node id="1""#));
+    assert!(!gexf.contains("<edge "));
+}
+
+#[test]
+fn regions_are_selected_by_name_or_id() {
+    let mut starmap = starmap();
+    starmap.systems[1].region_id = Some(20);
+    starmap.systems[1].region = Some("Rim".to_string());
+
+    assert_eq!(region_systems(&starmap.systems, "R<1>").unwrap(), [0]);
+    assert_eq!(region_systems(&starmap.systems, "20").unwrap(), [1]);
+    let err = region_systems(&starmap.systems, "Nowhere").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(err.to_string(), "no systems in region 'Nowhere'");
+}
+
+#[test]
+fn neighbourhoods_stop_at_the_jump_limit() {
+    // 0 - 1 - 2 - 3 - 4, with 5 on its own.
+    let starmap = common::starmap(6, &[(0, 1), (1, 2), (2, 3), (3, 4)]);
+    assert_eq!(neighbourhood(&starmap.graph, 0, 0), [0]);
+    assert_eq!(neighbourhood(&starmap.graph, 0, 2), [0, 1, 2]);
+    assert_eq!(neighbourhood(&starmap.graph, 2, 1), [1, 2, 3]);
+    assert_eq!(neighbourhood(&starmap.graph, 4, 10), [0, 1, 2, 3, 4]);
+    assert_eq!(neighbourhood(&starmap.graph, 5, 3), [5]);
+}
@@ -0,0 +1,49 @@
+//! Schema introspection of the checked-in fixture and of hand-built databases.
+
+use evefrontier_lib::inspect::inspect_connection;
+use evefrontier_lib::rusqlite::Connection;
+use evefrontier_lib::{inspect_dataset, SchemaVariant};
+use std::path::Path;
+
+#[test]
+fn fixture_has_membership_but_no_coordinates() {
+    let fixture =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../docs/fixtures/minimal_static_data.db");
+    let inspection = inspect_dataset(&fixture).unwrap();
+    assert_eq!(inspection.schema.variant, SchemaVariant::Partial);
+    assert!(inspection.schema.region_names && inspection.schema.constellation_names);
+    assert!(inspection.schema.coordinate_columns.is_none());
+
+    let systems = inspection
+        .tables
+        .iter()
+        .find(|t| t.name == "SolarSystems")
+        .unwrap();
+    assert_eq!(systems.rows, Some(3));
+    assert!(systems
+        .columns
+        .iter()
+        .any(|c| c.name == "solarSystemId" && c.primary_key));
+}
+
+#[test]
+fn reports_minimal_and_unsupported_schemas() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE SolarSystems(solarSystemId INTEGER PRIMARY KEY, name TEXT NOT NULL);
+         CREATE TABLE Jumps(fromSystemId INTEGER NOT NULL, toSystemId INTEGER NOT NULL);",
+    )
+    .unwrap();
+    assert_eq!(
+        inspect_connection(&conn).unwrap().schema.variant,
+        SchemaVariant::Minimal
+    );
+
+    conn.execute_batch("DROP TABLE Jumps").unwrap();
+    let inspection = inspect_connection(&conn).unwrap();
+    assert_eq!(inspection.schema.variant, SchemaVariant::Unsupported);
+    assert_eq!(
+        inspection.schema.problems,
+        vec!["in-memory database has no Jumps table".to_string()]
+    );
+}
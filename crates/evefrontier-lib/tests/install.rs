@@ -0,0 +1,266 @@
+//! Installing datasets from local files with `install_from`: archive kinds, sniffing files
+//! without a telling name, the separate cache directory for local assets and schema mappings.
+#![cfg(feature = "download")]
+
+use evefrontier_lib::rusqlite::Connection;
+use evefrontier_lib::{
+    install_from, list_cache, load_starmap, load_starmap_with, ArchiveKind, Config,
+    DatasetSelector, DownloadOptions, ErrorKind, NamePattern, SilentProgress,
+};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+fn fixture_db() -> Vec<u8> {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../docs/fixtures/minimal_static_data.db");
+    std::fs::read(path).expect("read fixture DB")
+}
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// A tar archive holding `entries` as `(path, contents)`.
+fn tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (path, contents) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, *contents).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (path, contents) in entries {
+        writer
+            .start_file(*path, zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(contents).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+fn assert_installed(db: &Path) {
+    let (systems, jumps) = load_starmap(db).expect("installed DB loads");
+    assert_eq!(systems.len(), 3);
+    assert_eq!(jumps.len(), 3);
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "evefrontier-install-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn options(dir: &Path) -> DownloadOptions {
+    DownloadOptions {
+        cache_root: Some(dir.join("cache")),
+        ..DownloadOptions::default()
+    }
+}
+
+/// A dataset whose tables and columns the loader cannot detect without a mapping.
+fn write_forked_dataset(path: &Path) {
+    let conn = Connection::open(path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE mapSolarSystems(solarSystemID INTEGER PRIMARY KEY, solarSystemName TEXT);
+         CREATE TABLE Stargates(source INTEGER, destination INTEGER);
+         INSERT INTO mapSolarSystems VALUES (1, 'Alpha'), (2, 'Beta');
+         INSERT INTO Stargates VALUES (1, 2);",
+    )
+    .unwrap();
+}
+
+const MAPPING: &str = r#"
+[schema.systems]
+table = "mapSolarSystems"
+id = "solarSystemID"
+name = "solarSystemName"
+
+[schema.jumps]
+table = "Stargates"
+from = "source"
+to = "destination"
+"#;
+
+#[test]
+fn renamed_columns_install_through_a_schema_mapping() {
+    let dir = temp_dir("mapping");
+    let source = dir.join("forked.db");
+    write_forked_dataset(&source);
+    let target = dir.join("data").join("static_data.db");
+    let source = source.to_str().unwrap();
+
+    let err = install_from(
+        source,
+        &target,
+        &DatasetSelector::default(),
+        &options(&dir),
+        &SilentProgress,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Schema);
+    assert!(!target.exists());
+
+    let config = Config::from_toml(MAPPING).unwrap();
+    let options = DownloadOptions {
+        schema: config.schema.clone(),
+        ..options(&dir)
+    };
+    let db = install_from(
+        source,
+        &target,
+        &DatasetSelector::default(),
+        &options,
+        &SilentProgress,
+    )
+    .unwrap();
+    assert_eq!(db, target);
+    let (systems, jumps) = load_starmap_with(&target, &config.schema).unwrap();
+    assert_eq!(systems.len(), 2);
+    assert_eq!(jumps.len(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn sniffing_tells_archives_apart_by_content() {
+    let dir = temp_dir("sniff");
+    let db = fixture_db();
+    let cases = [
+        ("db", db.clone(), Some(ArchiveKind::Db)),
+        ("zip", zip(&[("c3e6.db", &db)]), Some(ArchiveKind::Zip)),
+        ("tar", tar(&[("c3e6.db", &db)]), Some(ArchiveKind::Tar)),
+        (
+            "tgz",
+            gzip(&tar(&[("c3e6.db", &db)])),
+            Some(ArchiveKind::TarGz),
+        ),
+        ("gz", gzip(&db), Some(ArchiveKind::Gz)),
+        ("text", b"not a dataset".to_vec(), None),
+        ("empty", Vec::new(), None),
+    ];
+    for (name, bytes, expected) in cases {
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        assert_eq!(ArchiveKind::sniff(&path).unwrap(), expected, "{}", name);
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn every_archive_kind_installs_with_or_without_an_extension() {
+    let dir = temp_dir("kinds");
+    let db = fixture_db();
+    let other = b"SQLite format 3\0 but not the one wanted".to_vec();
+    let sources = [
+        ("static_data.db", db.clone()),
+        ("build.zip", zip(&[("data/c3e6.db", &db)])),
+        ("build.tar", tar(&[("readme.txt", b"hi"), ("c3e6.db", &db)])),
+        ("build.tar.gz", gzip(&tar(&[("data/c3e6.db", &db)]))),
+        ("build.db.gz", gzip(&db)),
+        ("sniffed-tarball", gzip(&tar(&[("c3e6.db", &db)]))),
+        ("sniffed-gzip", gzip(&db)),
+        (
+            "sniffed-zip",
+            zip(&[("other.db", &other), ("c3e6.db", &db)]),
+        ),
+    ];
+    for (name, bytes) in sources {
+        let source = dir.join("sources").join(name);
+        std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+        std::fs::write(&source, bytes).unwrap();
+        let target = dir.join("data").join(name).join("static_data.db");
+        install_from(
+            source.to_str().unwrap(),
+            &target,
+            &DatasetSelector::default(),
+            &options(&dir),
+            &SilentProgress,
+        )
+        .unwrap_or_else(|err| panic!("{}: {}", name, err));
+        assert_installed(&target);
+    }
+
+    // --entry picks among several databases.
+    let source = dir.join("sources").join("two.tar");
+    std::fs::write(&source, tar(&[("a.db", &db), ("b.db", &other)])).unwrap();
+    let target = dir.join("data").join("entry").join("static_data.db");
+    let selector = DatasetSelector {
+        entry: Some(NamePattern::glob("a.*").unwrap()),
+        ..DatasetSelector::default()
+    };
+    install_from(
+        source.to_str().unwrap(),
+        &target,
+        &selector,
+        &options(&dir),
+        &SilentProgress,
+    )
+    .unwrap();
+    assert_installed(&target);
+
+    let source = dir.join("sources").join("mystery");
+    std::fs::write(&source, b"not a dataset").unwrap();
+    let err = install_from(
+        source.to_str().unwrap(),
+        &target,
+        &DatasetSelector::default(),
+        &options(&dir),
+        &SilentProgress,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Extract);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn local_assets_never_replace_release_assets() {
+    let dir = temp_dir("namespace");
+    let cache = dir.join("cache");
+    std::fs::create_dir_all(&cache).unwrap();
+    // What the release downloader leaves behind for an asset named static_data.db.gz.
+    let release = cache.join("static_data.db.gz");
+    std::fs::write(&release, gzip(&fixture_db())).unwrap();
+    evefrontier_lib::cache::record_download(&release, Some("v1"), "https://example.com/a").unwrap();
+    let release_bytes = std::fs::read(&release).unwrap();
+
+    let source = dir.join("build").join("static_data.db.gz");
+    std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+    std::fs::write(&source, gzip(&fixture_db())).unwrap();
+    let target = dir.join("data").join("static_data.db");
+    install_from(
+        source.to_str().unwrap(),
+        &target,
+        &DatasetSelector::default(),
+        &options(&dir),
+        &SilentProgress,
+    )
+    .unwrap();
+    assert_installed(&target);
+
+    assert_eq!(std::fs::read(&release).unwrap(), release_bytes);
+    let entries = list_cache(&cache).unwrap();
+    let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["local/static_data.db.gz", "static_data.db.gz"]);
+    assert_eq!(entries[1].release_tag.as_deref(), Some("v1"));
+    assert_eq!(entries[0].release_tag, None);
+    assert!(entries[0].path.starts_with(cache.join("local")));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
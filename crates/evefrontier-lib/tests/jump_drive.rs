@@ -0,0 +1,82 @@
+//! Jump-drive edges from coordinates, and routes that mix them with gates.
+
+mod common;
+
+use evefrontier_lib::{
+    build_graph_with_jump_range, route_hops, shortest_path, ErrorKind, HopKind, Starmap,
+};
+use petgraph::graph::NodeIndex;
+
+/// Systems 0 to 3 on a line at 0, 1, 3 and 4 light-years, gated 0 - 1 and 2 - 3, plus system 4
+/// without coordinates, gated to 3.
+fn starmap() -> Starmap {
+    common::line(
+        &[Some(0.0), Some(1.0), Some(3.0), Some(4.0), None],
+        &[(0, 1), (2, 3), (3, 4)],
+    )
+}
+
+fn edge(graph: &evefrontier_lib::StarGraph, a: usize, b: usize) -> Option<HopKind> {
+    graph
+        .find_edge(NodeIndex::new(a), NodeIndex::new(b))
+        .map(|e| graph[e])
+}
+
+#[test]
+fn jump_edges_join_systems_in_range_without_a_gate() {
+    let starmap = starmap();
+    let graph = build_graph_with_jump_range(&starmap.systems, &starmap.jumps, 2.0).unwrap();
+    assert_eq!(edge(&graph, 0, 1), Some(HopKind::Gate));
+    assert_eq!(edge(&graph, 1, 2), Some(HopKind::JumpDrive));
+    assert_eq!(edge(&graph, 3, 4), Some(HopKind::Gate));
+    // 3 light-years is out of range, and system 4 cannot be measured.
+    assert_eq!(edge(&graph, 0, 2), None);
+    assert_eq!(graph.edge_count(), 4);
+
+    // The gate-only graph is untouched.
+    assert_eq!(starmap.graph.edge_count(), 3);
+    assert_eq!(starmap.jump_graph(2.0).unwrap().edge_count(), 4);
+}
+
+#[test]
+fn routes_mix_gates_and_jumps() {
+    let starmap = starmap();
+    assert!(shortest_path(&starmap.graph, 0, 4).is_none());
+
+    let graph = starmap.jump_graph(2.0).unwrap();
+    let route = shortest_path(&graph, 0, 4).unwrap();
+    assert_eq!(route, [0, 1, 2, 3, 4]);
+    let hops = route_hops(&starmap.systems, &graph, &route);
+    let kinds: Vec<HopKind> = hops.iter().map(|h| h.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            HopKind::Gate,
+            HopKind::JumpDrive,
+            HopKind::Gate,
+            HopKind::Gate
+        ]
+    );
+    assert!((hops[1].light_years.unwrap() - 2.0).abs() < 1e-9);
+    assert_eq!(hops[3].light_years, None);
+
+    // A longer range skips the gates where a single jump is as short.
+    let long = starmap.jump_graph(4.0).unwrap();
+    assert_eq!(shortest_path(&long, 0, 3).unwrap(), [0, 3]);
+}
+
+#[test]
+fn jump_range_must_be_positive_and_needs_coordinates() {
+    let starmap = starmap();
+    for range in [0.0, -1.0, f64::NAN] {
+        let err = starmap.jump_graph(range).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    let mut bare = starmap.systems.clone();
+    for s in &mut bare {
+        s.position = None;
+    }
+    let err = build_graph_with_jump_range(&bare, &starmap.jumps, 5.0).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
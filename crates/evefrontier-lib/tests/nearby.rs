@@ -0,0 +1,93 @@
+//! Jump and light-year neighbourhoods, grouped into rings.
+
+mod common;
+
+use evefrontier_lib::{nearby, ErrorKind, Reach, Starmap};
+
+/// Systems 0 to 3 on a line at 0, 1, 2 and 10 light-years, gated in a cycle 0 - 1 - 2 - 3 - 0,
+/// plus system 4 without coordinates, gated to 0.
+fn starmap() -> Starmap {
+    common::line(
+        &[Some(0.0), Some(1.0), Some(2.0), Some(10.0), None],
+        &[(0, 1), (1, 2), (2, 3), (3, 0), (0, 4)],
+    )
+}
+
+fn rings(found: &evefrontier_lib::Nearby) -> Vec<(usize, Vec<usize>)> {
+    found
+        .rings
+        .iter()
+        .map(|r| (r.index, r.systems.iter().map(|s| s.system).collect()))
+        .collect()
+}
+
+#[test]
+fn jump_rings_hold_systems_by_jump_count() {
+    let starmap = starmap();
+    let one = nearby(&starmap, 0, Reach::Jumps { max: 1 }).unwrap();
+    assert_eq!(rings(&one), [(0, vec![0]), (1, vec![1, 3, 4])]);
+    assert_eq!(one.count(), 3);
+
+    let two = nearby(&starmap, 0, Reach::Jumps { max: 2 }).unwrap();
+    assert_eq!(
+        rings(&two),
+        [(0, vec![0]), (1, vec![1, 3, 4]), (2, vec![2])]
+    );
+    assert!(two.rings[2].systems[0].light_years.is_none());
+
+    let none = nearby(&starmap, 2, Reach::Jumps { max: 0 }).unwrap();
+    assert_eq!(rings(&none), [(0, vec![2])]);
+    assert_eq!(none.count(), 0);
+}
+
+#[test]
+fn light_year_rings_follow_gate_travel_distance() {
+    let starmap = starmap();
+    let close = nearby(
+        &starmap,
+        0,
+        Reach::LightYears {
+            max: 5.0,
+            ring_width: 1.0,
+        },
+    )
+    .unwrap();
+    // System 3 is 10 light-years away either way round; system 4 cannot be measured.
+    assert_eq!(rings(&close), [(0, vec![0]), (1, vec![1]), (2, vec![2])]);
+    let far = &close.rings[2].systems[0];
+    assert_eq!(far.jumps, 2);
+    assert!((far.light_years.unwrap() - 2.0).abs() < 1e-9);
+
+    let wide = nearby(
+        &starmap,
+        0,
+        Reach::LightYears {
+            max: 10.0,
+            ring_width: 5.0,
+        },
+    )
+    .unwrap();
+    assert_eq!(rings(&wide), [(0, vec![0]), (1, vec![1, 2]), (2, vec![3])]);
+    assert_eq!(wide.rings[2].systems[0].jumps, 1);
+}
+
+#[test]
+fn light_years_need_coordinates_and_a_positive_ring_width() {
+    let starmap = starmap();
+    let reach = Reach::LightYears {
+        max: 5.0,
+        ring_width: 1.0,
+    };
+    let err = nearby(&starmap, 4, reach).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("S4 has no coordinates"));
+
+    let zero = Reach::LightYears {
+        max: 5.0,
+        ring_width: 0.0,
+    };
+    assert_eq!(
+        nearby(&starmap, 0, zero).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+}
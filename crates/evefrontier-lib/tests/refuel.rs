@@ -0,0 +1,113 @@
+//! Fuel-limited routes with refuel stops, and the refuel points file.
+
+mod common;
+
+use evefrontier_lib::refuel::parse_refuel_points;
+use evefrontier_lib::{refuel_route, ErrorKind, HopKind, ShipProfile, Starmap, System};
+
+/// Systems on the x axis at the given light-years, with the given gates between them. Ids
+/// start at 100 so they differ from the indices; names still count from `S0`.
+fn starmap(xs: &[f64], gates: &[(i64, i64)]) -> Starmap {
+    let systems = xs
+        .iter()
+        .zip(0..)
+        .map(|(x, index)| System {
+            id: 100 + index,
+            ..common::system_at(index, *x)
+        })
+        .collect();
+    let gates: Vec<_> = gates.iter().map(|&(a, b)| (100 + a, 100 + b)).collect();
+    Starmap::new(systems, common::jumps(&gates))
+}
+
+/// 10 fuel per light-year and a 40 fuel tank: at most 4 light-years on a full tank.
+fn ship() -> ShipProfile {
+    ShipProfile {
+        name: "Test".to_string(),
+        jump_range: 10.0,
+        fuel_capacity: 40.0,
+        fuel_per_ly: 1.0,
+        mass: 10.0,
+    }
+}
+
+#[test]
+fn stops_are_inserted_where_the_tank_runs_short() {
+    // 0 - 3 - 6 - 9 light-years: every hop burns 30 of the 40 in the tank.
+    let starmap = starmap(&[0.0, 3.0, 6.0, 9.0], &[]);
+    let found = refuel_route(&starmap, &ship(), &[1, 2], 0, 3).unwrap();
+    assert_eq!(found.route, [0, 1, 2, 3]);
+    assert!((found.total_fuel - 90.0).abs() < 1e-9);
+    let stops: Vec<usize> = found.stops.iter().map(|s| s.system).collect();
+    assert_eq!(stops, [1, 2]);
+    assert!((found.stops[0].fuel_left - 10.0).abs() < 1e-9);
+    let left: Vec<f64> = found.fuel_left.iter().map(|f| f.round()).collect();
+    assert_eq!(left, [10.0, 10.0, 10.0]);
+
+    let err = refuel_route(&starmap, &ship(), &[1], 0, 3).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NoRoute);
+    assert_eq!(err.to_string(), "no route from S0 to S3");
+}
+
+#[test]
+fn indices_outside_the_map_are_invalid_input() {
+    let starmap = starmap(&[0.0, 3.0, 6.0], &[]);
+    let err = refuel_route(&starmap, &ship(), &[1, 3], 0, 2).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(
+        err.to_string(),
+        "refuel point 3 is not a system index; the star map has 3 systems"
+    );
+    let err = refuel_route(&starmap, &ship(), &[1], 0, 7).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(err.to_string().starts_with("goal 7 is not a system index"));
+}
+
+#[test]
+fn refuel_points_on_the_way_are_passed_when_the_tank_suffices() {
+    // Systems at 0, 1, 2 and 5 light-years, gated 0 - 1 - 2. Gating to 2 and jumping the last
+    // 3 light-years burns 30, less than jumping 4 from system 1, and the tank covers it.
+    let starmap = starmap(&[0.0, 1.0, 2.0, 5.0], &[(0, 1), (1, 2)]);
+    let found = refuel_route(&starmap, &ship(), &[1, 2], 0, 3).unwrap();
+    assert_eq!(found.route, [0, 1, 2, 3]);
+    assert_eq!(found.fuel_per_hop, [0.0, 0.0, found.total_fuel]);
+    assert!((found.total_fuel - 30.0).abs() < 1e-9);
+    assert!(found.stops.is_empty());
+}
+
+#[test]
+fn a_detour_through_a_gate_to_refuel_is_found() {
+    // 0 - 4 - 8 light-years by jump drive; system 3 is far away but gated to system 1, so the
+    // ship has to go there and back to fill up.
+    let starmap = starmap(&[0.0, 4.0, 8.0, 100.0], &[(1, 3)]);
+    let found = refuel_route(&starmap, &ship(), &[3], 0, 2).unwrap();
+    assert_eq!(found.route, [0, 1, 3, 1, 2]);
+    let kinds: Vec<HopKind> = found.hops.iter().map(|h| h.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            HopKind::JumpDrive,
+            HopKind::Gate,
+            HopKind::Gate,
+            HopKind::JumpDrive
+        ]
+    );
+    assert_eq!(found.stops.len(), 1);
+    assert_eq!((found.stops[0].position, found.stops[0].system), (2, 3));
+    assert!((found.total_fuel - 80.0).abs() < 1e-9);
+}
+
+#[test]
+fn refuel_points_are_read_by_name_or_id() {
+    let starmap = starmap(&[0.0, 1.0, 2.0], &[]);
+    let text = "# system, label\nS2, station\n\n101 , SSU \"Depot\"\nS2\n";
+    assert_eq!(parse_refuel_points(text, &starmap.systems).unwrap(), [2, 1]);
+
+    let err = parse_refuel_points("S0\nNowhere, station\n", &starmap.systems).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(err.to_string(), "line 2");
+    assert_eq!(
+        std::error::Error::source(&err).unwrap().to_string(),
+        "unknown system 'Nowhere'"
+    );
+}
@@ -0,0 +1,66 @@
+//! Loading datasets whose tables and columns are renamed, through a config-file schema mapping.
+
+use evefrontier_lib::rusqlite::Connection;
+use evefrontier_lib::{load_starmap_from_connection_with, Config, ErrorKind};
+
+fn forked_dataset() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE mapSolarSystems(solarSystemID INTEGER PRIMARY KEY, solarSystemName TEXT,
+             regionID INTEGER, centerX REAL, centerY REAL, centerZ REAL);
+         CREATE TABLE Stargates(source INTEGER, destination INTEGER);
+         CREATE TABLE mapRegions(regionID INTEGER, regionName TEXT);
+         INSERT INTO mapSolarSystems VALUES (1, 'Alpha', 9, 0, 0, 0), (2, 'Beta', 9, 1, 2, 3);
+         INSERT INTO Stargates VALUES (1, 2);
+         INSERT INTO mapRegions VALUES (9, 'Nine');",
+    )
+    .unwrap();
+    conn
+}
+
+const MAPPING: &str = r#"
+[schema.systems]
+table = "mapSolarSystems"
+id = "solarSystemID"
+name = "solarSystemName"
+
+[schema.jumps]
+table = "Stargates"
+from = "source"
+to = "destination"
+
+[schema.regions]
+table = "mapRegions"
+"#;
+
+#[test]
+fn mapped_names_take_precedence_and_the_rest_is_detected() {
+    let config = Config::from_toml(MAPPING).unwrap();
+    let (systems, jumps) =
+        load_starmap_from_connection_with(&forked_dataset(), &config.schema).unwrap();
+    assert_eq!(systems.len(), 2);
+    assert_eq!(systems[1].name, "Beta");
+    assert_eq!(systems[1].region.as_deref(), Some("Nine"));
+    assert_eq!(systems[1].position.map(|p| p.z), Some(3.0));
+    assert_eq!((jumps[0].from_id, jumps[0].to_id), (1, 2));
+}
+
+#[test]
+fn mapped_column_that_does_not_exist_is_reported() {
+    let config = Config::from_toml(&MAPPING.replace("\"destination\"", "\"dest\"")).unwrap();
+    let err = load_starmap_from_connection_with(&forked_dataset(), &config.schema).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Schema);
+    assert_eq!(
+        err.to_string(),
+        "schema mapping jumps.to = \"dest\" is not a column of Stargates in in-memory database \
+         (columns: source, destination)"
+    );
+}
+
+#[test]
+fn invalid_mappings_are_rejected_when_parsed() {
+    let err = Config::from_toml("[schema.systems]\nx = \"posX\"\n").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = Config::from_toml("[schema.jumps]\nsource = \"a\"\n").unwrap_err();
+    assert!(err.to_string().contains("unknown field `source`"));
+}
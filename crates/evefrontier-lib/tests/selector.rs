@@ -0,0 +1,116 @@
+//! Choosing the release asset and the archive entry with glob and regex name patterns.
+#![cfg(feature = "download")]
+
+use evefrontier_lib::{DatasetSelector, ErrorKind, NamePattern, ReleaseAsset};
+
+fn assets(names: &[&str]) -> Vec<ReleaseAsset> {
+    names
+        .iter()
+        .map(|name| ReleaseAsset {
+            name: name.to_string(),
+            size: 0,
+            download_url: format!("https://example.com/{}", name),
+        })
+        .collect()
+}
+
+fn entries(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+fn selector(asset: Option<NamePattern>, entry: Option<NamePattern>) -> DatasetSelector {
+    DatasetSelector { asset, entry }
+}
+
+#[test]
+fn patterns_match_the_whole_name_or_its_last_component() {
+    let glob = NamePattern::glob("c3e6*.db").unwrap();
+    assert!(glob.matches("c3e6.db"));
+    assert!(glob.matches("data/c3e6-cycle5.db"));
+    assert!(!glob.matches("static_c3e6.db"));
+    assert_eq!(glob.to_string(), "c3e6*.db");
+
+    let regex = NamePattern::regex(r"cycle\d+").unwrap();
+    assert!(regex.matches("static_cycle5.zip"));
+    assert!(!regex.matches("static_cycle.zip"));
+    assert_eq!(regex.to_string(), r"/cycle\d+/");
+
+    assert_eq!(
+        NamePattern::glob("[").unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        NamePattern::regex("(").unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn assets_are_chosen_among_dataset_files_only() {
+    let release = assets(&["notes.txt", "cycle4.zip", "cycle5.zip", "cycle5.db.gz"]);
+
+    let default = selector(None, None).select_asset(&release).unwrap();
+    assert_eq!(default.name, "cycle4.zip");
+
+    let glob = selector(Some(NamePattern::glob("*.db.gz").unwrap()), None);
+    assert_eq!(glob.select_asset(&release).unwrap().name, "cycle5.db.gz");
+    let regex = selector(Some(NamePattern::regex(r"^cycle4\.").unwrap()), None);
+    assert_eq!(regex.select_asset(&release).unwrap().name, "cycle4.zip");
+
+    // The text file matches but is not a dataset.
+    let none = selector(Some(NamePattern::glob("notes*").unwrap()), None);
+    let err = none.select_asset(&release).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Download);
+    assert!(err
+        .to_string()
+        .contains("No dataset asset matches 'notes*'"));
+
+    let ambiguous = selector(Some(NamePattern::glob("cycle5*").unwrap()), None);
+    let err = ambiguous.select_asset(&release).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Download);
+    assert!(err
+        .to_string()
+        .ends_with("is ambiguous, it matches: cycle5.zip, cycle5.db.gz"));
+
+    let err = selector(None, None)
+        .select_asset(&assets(&["notes.txt"]))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Download);
+}
+
+#[test]
+fn entries_prefer_c3e6_unless_a_pattern_says_otherwise() {
+    let archive = entries(&["readme.txt", "data/other.db", "data/c3e6.db", "extra.db"]);
+
+    assert_eq!(selector(None, None).select_entry(&archive).unwrap(), 2);
+    assert_eq!(
+        selector(None, None)
+            .select_entry(&entries(&["b.db", "a.db"]))
+            .unwrap(),
+        0
+    );
+
+    let glob = selector(None, Some(NamePattern::glob("other.*").unwrap()));
+    assert_eq!(glob.select_entry(&archive).unwrap(), 1);
+    let regex = selector(None, Some(NamePattern::regex("^extra").unwrap()));
+    assert_eq!(regex.select_entry(&archive).unwrap(), 3);
+
+    let none = selector(None, Some(NamePattern::glob("readme*").unwrap()));
+    let err = none.select_entry(&archive).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Extract);
+    assert!(err
+        .to_string()
+        .contains("No .db entry inside the archive matches 'readme*'"));
+
+    let ambiguous = selector(None, Some(NamePattern::glob("*.db").unwrap()));
+    let err = ambiguous.select_entry(&archive).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Extract);
+    assert!(err
+        .to_string()
+        .ends_with("it matches: data/other.db, data/c3e6.db, extra.db"));
+
+    let err = selector(None, None)
+        .select_entry(&entries(&["readme.txt"]))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Extract);
+}
@@ -0,0 +1,114 @@
+//! Ship profiles from the config file, their effective range and fuel use along a route.
+
+mod common;
+
+use evefrontier_lib::{route_hops, shortest_path, Config, ErrorKind, Starmap};
+
+const SHIPS: &str = r#"
+[[ships]]
+name = "Scout"
+jump_range = 2.5
+fuel_capacity = 100.0
+fuel_per_ly = 2.0
+mass = 10.0
+
+[[ships]]
+name = "Hauler"
+jump_range = 5.0
+fuel_capacity = 30.0
+fuel_per_ly = 1.0
+mass = 10.0
+"#;
+
+/// Systems 0 to 3 on a line at 0, 1, 3 and 4 light-years, gated 0 - 1 and 2 - 3.
+fn starmap() -> Starmap {
+    common::line(
+        &[Some(0.0), Some(1.0), Some(3.0), Some(4.0)],
+        &[(0, 1), (2, 3)],
+    )
+}
+
+#[test]
+fn ships_load_from_config_and_are_found_by_name() {
+    let config = Config::from_toml(SHIPS).unwrap();
+    assert_eq!(config.ships.len(), 2);
+    let scout = config.ship("Scout").unwrap();
+    assert_eq!(scout.jump_range, 2.5);
+    assert_eq!(scout.fuel_for(2.0), 40.0);
+
+    let err = config.ship("Nope").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("configured: Scout, Hauler"));
+    let none = Config::default().ship("Scout").unwrap_err();
+    assert!(none.to_string().contains("defines no [[ships]]"));
+}
+
+#[test]
+fn invalid_ships_are_rejected() {
+    let duplicate = SHIPS.replace("\"Hauler\"", "\"Scout\"");
+    let err = Config::from_toml(&duplicate).unwrap_err();
+    assert!(err.to_string().contains("Scout is defined more than once"));
+
+    let negative = SHIPS.replace("mass = 10.0", "mass = -1.0");
+    let err = Config::from_toml(&negative).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("mass = -1 is out of range"));
+
+    // An empty tank could not make any jump, unless jumps burn no fuel.
+    let empty = SHIPS.replace("fuel_capacity = 30.0", "fuel_capacity = 0.0");
+    let err = Config::from_toml(&empty).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(err
+        .to_string()
+        .contains("Hauler: fuel_capacity = 0 leaves no fuel"));
+    let free = empty.replace("fuel_per_ly = 1.0", "fuel_per_ly = 0.0");
+    assert_eq!(
+        Config::from_toml(&free)
+            .unwrap()
+            .ship("Hauler")
+            .unwrap()
+            .effective_range(),
+        5.0
+    );
+
+    let missing = SHIPS.replace("fuel_per_ly = 2.0\n", "");
+    assert_eq!(
+        Config::from_toml(&missing).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    let unknown = SHIPS.replace("mass = 10.0", "mass = 10.0\ncargo = 1.0");
+    assert_eq!(
+        Config::from_toml(&unknown).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn range_is_limited_by_a_full_tank_and_fuel_is_counted_per_jump() {
+    let config = Config::from_toml(SHIPS).unwrap();
+    let (scout, hauler) = (
+        config.ship("Scout").unwrap(),
+        config.ship("Hauler").unwrap(),
+    );
+    assert_eq!(scout.effective_range(), 2.5);
+    // 30 fuel at 10 per light-year reaches 3 light-years, short of the 5 the drive allows.
+    assert_eq!(hauler.effective_range(), 3.0);
+
+    let starmap = starmap();
+    let graph = starmap.jump_graph(scout.effective_range()).unwrap();
+    let route = shortest_path(&graph, 0, 3).unwrap();
+    assert_eq!(route, [0, 1, 2, 3]);
+    let fuel = scout.fuel_use(&route_hops(&starmap.systems, &graph, &route));
+    assert_eq!(fuel.per_hop.len(), 3);
+    assert_eq!(fuel.per_hop[0], 0.0);
+    assert!((fuel.per_hop[1] - 40.0).abs() < 1e-9);
+    assert!((fuel.total - 40.0).abs() < 1e-9);
+    assert!(!fuel.exceeds_capacity());
+
+    let graph = starmap.jump_graph(hauler.effective_range()).unwrap();
+    let route = shortest_path(&graph, 0, 3).unwrap();
+    assert_eq!(route, [0, 2, 3]);
+    let fuel = hauler.fuel_use(&route_hops(&starmap.systems, &graph, &route));
+    assert!((fuel.total - 30.0).abs() < 1e-9);
+    assert!(!fuel.exceeds_capacity());
+}
@@ -0,0 +1,178 @@
+//! When `<db>.snapshot.bin` is reused and when it is rebuilt.
+
+use evefrontier_lib::snapshot::{read_snapshot, snapshot_path, write_snapshot};
+use evefrontier_lib::{Config, ErrorKind, SchemaMapping, Starmap, SNAPSHOT_VERSION};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "evefrontier-snapshot-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A copy of the fixture DB in `dir`, so its snapshot and timestamps are the test's own.
+fn fixture_copy(dir: &Path) -> PathBuf {
+    let fixture =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../docs/fixtures/minimal_static_data.db");
+    let db = dir.join("static_data.db");
+    fs::copy(fixture, &db).unwrap();
+    db
+}
+
+/// A star map that differs from the fixture, so a load that returns it must have come from
+/// the snapshot.
+fn marker_starmap(db: &Path) -> Starmap {
+    let full = Starmap::from_db(db).unwrap();
+    Starmap::new(full.systems[..1].to_vec(), Vec::new())
+}
+
+fn touch(path: &Path, secs_ahead: u64) {
+    let file = File::options().write(true).open(path).unwrap();
+    let when = SystemTime::now() + Duration::from_secs(secs_ahead);
+    file.set_modified(when).unwrap();
+}
+
+fn fresh(db: &Path) -> bool {
+    read_snapshot(db, &SchemaMapping::default())
+        .unwrap()
+        .is_some()
+}
+
+#[test]
+fn a_fresh_snapshot_is_loaded_instead_of_the_db() {
+    let dir = temp_dir("fresh");
+    let db = fixture_copy(&dir);
+    assert!(!fresh(&db));
+
+    let loaded = Starmap::load(&db).unwrap();
+    assert_eq!(loaded.systems.len(), 3);
+    assert!(snapshot_path(&db).is_file());
+    assert!(fresh(&db));
+
+    write_snapshot(&db, &SchemaMapping::default(), &marker_starmap(&db)).unwrap();
+    let loaded = Starmap::load(&db).unwrap();
+    assert_eq!(loaded.systems.len(), 1);
+    assert_eq!(loaded.graph.node_count(), 1);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn version_and_mapping_mismatches_rebuild_the_snapshot() {
+    let dir = temp_dir("version");
+    let db = fixture_copy(&dir);
+    let path = snapshot_path(&db);
+
+    write_snapshot(&db, &SchemaMapping::default(), &marker_starmap(&db)).unwrap();
+    // The version follows the eight-byte magic number as a little-endian u32.
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[8..12].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+    fs::write(&path, bytes).unwrap();
+    assert!(!fresh(&db));
+    assert_eq!(Starmap::load(&db).unwrap().systems.len(), 3);
+    assert!(fresh(&db));
+
+    let config = Config::from_toml(
+        r#"
+[schema.systems]
+table = "SolarSystems"
+id = "solarSystemId"
+name = "name"
+"#,
+    )
+    .unwrap();
+    assert_ne!(config.schema, SchemaMapping::default());
+    assert!(read_snapshot(&db, &config.schema).unwrap().is_none());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn a_changed_db_invalidates_the_snapshot() {
+    let dir = temp_dir("changed");
+    let db = dir.join("data.db");
+    let mapping = SchemaMapping::default();
+    let starmap = Starmap::new(Vec::new(), Vec::new());
+
+    // Only the file's size, timestamp and digest matter to freshness.
+    fs::write(&db, b"first contents").unwrap();
+    write_snapshot(&db, &mapping, &starmap).unwrap();
+    assert!(fresh(&db));
+
+    // A new modification time with the same bytes keeps the snapshot, via the digest.
+    touch(&db, 60);
+    assert!(fresh(&db));
+
+    // Same size, different bytes and timestamp.
+    fs::write(&db, b"other contents").unwrap();
+    touch(&db, 120);
+    assert!(!fresh(&db));
+
+    // A different size is stale whatever the timestamp.
+    fs::write(&db, b"first contents").unwrap();
+    write_snapshot(&db, &mapping, &starmap).unwrap();
+    let modified = fs::metadata(&db).unwrap().modified().unwrap();
+    fs::write(&db, b"first contents, grown").unwrap();
+    File::options()
+        .write(true)
+        .open(&db)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+    assert!(!fresh(&db));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn corrupt_snapshots_are_errors_that_load_rebuilds_from() {
+    let dir = temp_dir("corrupt");
+    let db = fixture_copy(&dir);
+    let path = snapshot_path(&db);
+    let mapping = SchemaMapping::default();
+    write_snapshot(&db, &mapping, &marker_starmap(&db)).unwrap();
+    let intact = fs::read(&path).unwrap();
+
+    // A foreign file without the magic number is simply not a snapshot.
+    fs::write(&path, b"not a snapshot at all").unwrap();
+    assert!(read_snapshot(&db, &mapping).unwrap().is_none());
+
+    let cases = [
+        ("header", &intact[..14]),
+        ("body", &intact[..intact.len() - 8]),
+    ];
+    for (part, bytes) in cases {
+        fs::write(&path, bytes).unwrap();
+        let err = read_snapshot(&db, &mapping).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io, "{}", part);
+        assert!(err.to_string().contains("corrupt snapshot"), "{}", part);
+
+        assert_eq!(Starmap::load(&db).unwrap().systems.len(), 3, "{}", part);
+        assert!(fresh(&db), "{}", part);
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[cfg(feature = "download")]
+#[test]
+fn removing_a_cached_db_removes_its_snapshot() {
+    let dir = temp_dir("cache");
+    let db = fixture_copy(&dir);
+    Starmap::load(&db).unwrap();
+    assert!(snapshot_path(&db).is_file());
+
+    let removed = evefrontier_lib::clear_cache(&dir, &[]).unwrap();
+    assert_eq!(removed.len(), 1);
+    assert!(!db.exists());
+    assert!(!snapshot_path(&db).exists());
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,205 @@
+//! JSON and CSV star map sources: a round trip of the fixture dataset through each, and
+//! malformed files.
+
+use evefrontier_lib::{
+    load_starmap, open_source, CsvSource, ErrorKind, JsonSource, Jump, SourceFormat, StarmapSource,
+    System,
+};
+use std::path::{Path, PathBuf};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "evefrontier-sources-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// The fixture dataset, with constellations and positions added so every field round-trips.
+fn fixture() -> (Vec<System>, Vec<Jump>) {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../docs/fixtures/minimal_static_data.db");
+    let (mut systems, jumps) = load_starmap(&path).unwrap();
+    for (i, system) in systems.iter_mut().enumerate() {
+        system.constellation = Some(format!("Constellation {}", i));
+        system.position = Some(evefrontier_lib::Position {
+            x: 1.0e17 * i as f64,
+            y: -2.5e16,
+            z: 0.125,
+        });
+    }
+    (systems, jumps)
+}
+
+/// Systems and jumps as JSON values, since neither type implements `PartialEq`.
+fn values(starmap: &(Vec<System>, Vec<Jump>)) -> serde_json::Value {
+    serde_json::json!({ "systems": starmap.0, "jumps": starmap.1 })
+}
+
+fn write_csv(systems_path: &Path, jumps_path: &Path, (systems, jumps): &(Vec<System>, Vec<Jump>)) {
+    let mut writer = csv::Writer::from_path(systems_path).unwrap();
+    writer
+        .write_record([
+            "id",
+            "name",
+            "region_id",
+            "region",
+            "constellation_id",
+            "constellation",
+            "x",
+            "y",
+            "z",
+        ])
+        .unwrap();
+    let text = |v: Option<String>| v.unwrap_or_default();
+    for s in systems {
+        let p = s.position;
+        writer
+            .write_record([
+                s.id.to_string(),
+                s.name.clone(),
+                text(s.region_id.map(|v| v.to_string())),
+                text(s.region.clone()),
+                text(s.constellation_id.map(|v| v.to_string())),
+                text(s.constellation.clone()),
+                text(p.map(|p| p.x.to_string())),
+                text(p.map(|p| p.y.to_string())),
+                text(p.map(|p| p.z.to_string())),
+            ])
+            .unwrap();
+    }
+    writer.flush().unwrap();
+    let mut writer = csv::Writer::from_path(jumps_path).unwrap();
+    writer.write_record(["from_id", "to_id"]).unwrap();
+    for j in jumps {
+        writer
+            .write_record([j.from_id.to_string(), j.to_id.to_string()])
+            .unwrap();
+    }
+    writer.flush().unwrap();
+}
+
+#[test]
+fn json_round_trips_the_fixture() {
+    let dir = temp_dir("json");
+    let original = fixture();
+    let path = dir.join("map.json");
+    std::fs::write(&path, values(&original).to_string()).unwrap();
+
+    let loaded = JsonSource::new(&path).load().unwrap();
+    assert_eq!(values(&loaded), values(&original));
+    let opened = open_source(&path, None).load().unwrap();
+    assert_eq!(values(&opened), values(&original));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn csv_round_trips_the_fixture_as_a_pair_or_a_directory() {
+    let dir = temp_dir("csv");
+    let original = fixture();
+    write_csv(&dir.join("map.csv"), &dir.join("map.jumps.csv"), &original);
+    let loaded = open_source(&dir.join("map.csv"), None).load().unwrap();
+    assert_eq!(values(&loaded), values(&original));
+
+    let folder = dir.join("folder");
+    std::fs::create_dir_all(&folder).unwrap();
+    write_csv(
+        &folder.join("systems.csv"),
+        &folder.join("jumps.csv"),
+        &original,
+    );
+    assert_eq!(SourceFormat::from_path(&folder), Some(SourceFormat::Csv));
+    let loaded = CsvSource::from_path(&folder).load().unwrap();
+    assert_eq!(values(&loaded), values(&original));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn optional_columns_may_be_left_out() {
+    let dir = temp_dir("minimal");
+    std::fs::write(dir.join("systems.csv"), "id,name\n1,Alpha\n2,Beta\n").unwrap();
+    std::fs::write(dir.join("jumps.csv"), "from_id,to_id\n1,2\n").unwrap();
+    let (systems, jumps) = CsvSource::from_path(&dir).load().unwrap();
+    assert_eq!(systems.len(), 2);
+    assert!(systems[1].region.is_none() && systems[1].position.is_none());
+    assert_eq!((jumps[0].from_id, jumps[0].to_id), (1, 2));
+
+    let path = dir.join("map.json");
+    std::fs::write(
+        &path,
+        r#"{"systems": [{"id": 1, "name": "Alpha"}], "jumps": []}"#,
+    )
+    .unwrap();
+    let (systems, jumps) = JsonSource::new(&path).load().unwrap();
+    assert_eq!(systems[0].name, "Alpha");
+    assert!(jumps.is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn malformed_rows_are_invalid_input() {
+    let dir = temp_dir("malformed");
+    let json_cases = [
+        ("not-json", "systems: []"),
+        ("no-jumps", r#"{"systems": []}"#),
+        ("no-id", r#"{"systems": [{"name": "Alpha"}], "jumps": []}"#),
+        (
+            "text-id",
+            r#"{"systems": [{"id": "one", "name": "Alpha"}], "jumps": []}"#,
+        ),
+        (
+            "bad-jump",
+            r#"{"systems": [{"id": 1, "name": "Alpha"}], "jumps": [{"from_id": 1}]}"#,
+        ),
+    ];
+    for (name, text) in json_cases {
+        let path = dir.join(format!("{}.json", name));
+        std::fs::write(&path, text).unwrap();
+        let err = JsonSource::new(&path).load().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput, "{}", name);
+        assert!(
+            err.to_string().contains("is not a JSON star map"),
+            "{}",
+            name
+        );
+    }
+
+    let csv_cases = [
+        ("text-id", "id,name\none,Alpha\n", "from_id,to_id\n"),
+        ("no-name", "id\n1\n", "from_id,to_id\n"),
+        (
+            "short-row",
+            "id,name,x\n1,Alpha,1.0\n2\n",
+            "from_id,to_id\n",
+        ),
+        ("bad-jump", "id,name\n1,Alpha\n", "from_id,to_id\n1,north\n"),
+    ];
+    for (name, systems, jumps) in csv_cases {
+        let path = dir.join(format!("{}.csv", name));
+        std::fs::write(&path, systems).unwrap();
+        std::fs::write(dir.join(format!("{}.jumps.csv", name)), jumps).unwrap();
+        let err = CsvSource::from_path(&path).load().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput, "{}", name);
+        assert!(
+            err.to_string()
+                .contains("does not match the CSV star map schema"),
+            "{}",
+            name
+        );
+    }
+
+    // A missing jumps file is also the caller's mistake.
+    let path = dir.join("lonely.csv");
+    std::fs::write(&path, "id,name\n1,Alpha\n").unwrap();
+    let err = CsvSource::from_path(&path).load().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("lonely.jumps.csv does not exist"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
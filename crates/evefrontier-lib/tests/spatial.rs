@@ -0,0 +1,119 @@
+//! KD-tree queries checked against a linear scan.
+
+mod common;
+
+use evefrontier_lib::{ErrorKind, Position, SpatialIndex, System, LIGHT_YEAR};
+
+/// A deterministic scatter of systems in a 100 light-year cube, with every tenth system on a
+/// shared grid point so that equal distances occur, and every seventh without coordinates.
+fn systems(count: usize) -> Vec<System> {
+    let mut state: u64 = 42;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 11) as f64 / (1u64 << 53) as f64 * 100.0
+    };
+    (0..count)
+        .map(|i| {
+            let (x, y, z) = if i % 10 == 0 {
+                (50.0, 50.0, (i % 3) as f64)
+            } else {
+                (next(), next(), next())
+            };
+            System {
+                position: (i % 7 != 0).then_some(Position {
+                    x: x * LIGHT_YEAR,
+                    y: y * LIGHT_YEAR,
+                    z: z * LIGHT_YEAR,
+                }),
+                ..common::system(i as i64)
+            }
+        })
+        .collect()
+}
+
+fn scan(systems: &[System], point: Position) -> Vec<(usize, f64)> {
+    let mut all: Vec<(usize, f64)> = systems
+        .iter()
+        .enumerate()
+        .filter_map(|(i, s)| s.position.map(|p| (i, p.distance(point) / LIGHT_YEAR)))
+        .collect();
+    all.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+    all
+}
+
+#[test]
+fn queries_match_a_linear_scan() {
+    let systems = systems(2_000);
+    let index = SpatialIndex::new(&systems);
+    assert_eq!(
+        index.len(),
+        systems.iter().filter(|s| s.position.is_some()).count()
+    );
+
+    let points = [
+        (50.0, 50.0, 1.0),
+        (0.0, 0.0, 0.0),
+        (99.0, 3.0, 47.5),
+        (-20.0, 130.0, 50.0),
+    ];
+    for (x, y, z) in points {
+        let point = Position {
+            x: x * LIGHT_YEAR,
+            y: y * LIGHT_YEAR,
+            z: z * LIGHT_YEAR,
+        };
+        let expected = scan(&systems, point);
+        for k in [1, 5, 40] {
+            let found: Vec<usize> = index.nearest(point, k).iter().map(|n| n.system).collect();
+            let want: Vec<usize> = expected.iter().take(k).map(|e| e.0).collect();
+            assert_eq!(found, want, "nearest {} to {:?}", k, (x, y, z));
+        }
+        for radius in [0.0, 5.0, 25.0] {
+            let found = index.within(point, radius).unwrap();
+            let want: Vec<usize> = expected
+                .iter()
+                .filter(|e| e.1 <= radius + 1e-9)
+                .map(|e| e.0)
+                .collect();
+            assert_eq!(
+                found.iter().map(|n| n.system).collect::<Vec<_>>(),
+                want,
+                "within {} of {:?}",
+                radius,
+                (x, y, z)
+            );
+            for (n, e) in found.iter().zip(&expected) {
+                assert!((n.light_years - e.1).abs() < 1e-9);
+            }
+        }
+    }
+}
+
+#[test]
+fn empty_index_finds_nothing() {
+    let index = SpatialIndex::new(&[]);
+    assert!(index.is_empty());
+    let origin = Position {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    assert!(index.nearest(origin, 3).is_empty());
+    assert!(index.within(origin, 1e6).unwrap().is_empty());
+}
+
+#[test]
+fn negative_or_non_finite_radii_are_rejected() {
+    let index = SpatialIndex::new(&systems(20));
+    let origin = Position {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    for radius in [-1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let err = index.within(origin, radius).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput, "{}", radius);
+    }
+}
@@ -0,0 +1,94 @@
+//! Whole-map statistics on a small hand-checked map.
+
+use evefrontier_lib::stats::{DegreeCount, RegionStats};
+mod common;
+
+use evefrontier_lib::{graph_stats, Starmap, System};
+
+fn system(id: i64, region: Option<&str>) -> System {
+    System {
+        region: region.map(str::to_string),
+        ..common::system(id)
+    }
+}
+
+#[test]
+fn counts_distances_and_regions() {
+    // R1: 0 - 1 - 2, bordering R2: 3 - 4; R2 also holds 5, linked to the region-less 6, and
+    // the isolated 7.
+    let regions = [
+        Some("R1"),
+        Some("R1"),
+        Some("R1"),
+        Some("R2"),
+        Some("R2"),
+        Some("R2"),
+        None,
+        Some("R2"),
+    ];
+    let systems = regions
+        .iter()
+        .enumerate()
+        .map(|(id, region)| system(id as i64, *region))
+        .collect();
+    let jumps = common::jumps(&[(0, 1), (1, 0), (1, 2), (2, 3), (3, 4), (5, 6)]);
+    let stats = graph_stats(&Starmap::new(systems, jumps));
+
+    assert_eq!(stats.systems, 8);
+    assert_eq!(stats.jumps, 5);
+    assert_eq!(stats.components, 3);
+    assert_eq!(stats.largest_component, 5);
+    assert_eq!(stats.isolated_systems, 1);
+    assert_eq!((stats.degree.min, stats.degree.max), (0, 2));
+    assert_eq!(stats.degree.mean, 10.0 / 8.0);
+    assert_eq!(
+        stats.degree.distribution,
+        [
+            DegreeCount {
+                degree: 0,
+                systems: 1
+            },
+            DegreeCount {
+                degree: 1,
+                systems: 4
+            },
+            DegreeCount {
+                degree: 2,
+                systems: 3
+            },
+        ]
+    );
+    assert_eq!(stats.diameter, 4);
+    assert_eq!(stats.radius, 2);
+    // The chain of five has 20 ordered pairs at 40 jumps in total; the pair 5 - 6 adds 2 and 2.
+    assert!((stats.average_path_length - 42.0 / 22.0).abs() < 1e-12);
+
+    let region = |name: Option<&str>, systems, internal_jumps, border_jumps, components, degree| {
+        RegionStats {
+            region: name.map(str::to_string),
+            systems,
+            internal_jumps,
+            border_jumps,
+            components,
+            average_degree: degree,
+        }
+    };
+    assert_eq!(
+        stats.regions,
+        [
+            region(None, 1, 0, 1, 1, 1.0),
+            region(Some("R1"), 3, 2, 1, 1, 5.0 / 3.0),
+            region(Some("R2"), 4, 1, 2, 3, 1.0),
+        ]
+    );
+}
+
+#[test]
+fn empty_map_has_zero_statistics() {
+    let stats = graph_stats(&Starmap::new(Vec::new(), Vec::new()));
+    assert_eq!(stats.systems, 0);
+    assert_eq!(stats.components, 0);
+    assert_eq!((stats.diameter, stats.radius), (0, 0));
+    assert_eq!(stats.average_path_length, 0.0);
+    assert!(stats.regions.is_empty());
+}
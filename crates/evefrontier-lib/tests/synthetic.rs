@@ -0,0 +1,135 @@
+//! Synthetic galaxies: determinism, connectivity, and round trips through the SQLite schemas.
+#![cfg(feature = "synthetic")]
+
+use evefrontier_lib::rusqlite::Connection;
+use evefrontier_lib::synthetic::{write_sqlite, write_to_connection};
+use evefrontier_lib::{
+    generate, load_starmap, optimal_route, shortest_path, GalaxyConfig, Starmap, SyntheticSchema,
+};
+use std::collections::HashSet;
+
+fn large() -> GalaxyConfig {
+    GalaxyConfig {
+        seed: 7,
+        systems: 20_000,
+        regions: 100,
+        constellations_per_region: 20,
+        ..GalaxyConfig::default()
+    }
+}
+
+fn is_connected(starmap: &Starmap) -> bool {
+    petgraph::algo::connected_components(&starmap.graph) == 1
+}
+
+#[test]
+fn same_seed_gives_the_same_galaxy() {
+    let config = GalaxyConfig::default();
+    let a = generate(&config);
+    let b = generate(&config);
+    assert_eq!(a.systems.len(), 1_000);
+    assert_eq!(
+        a.systems.iter().map(|s| &s.name).collect::<Vec<_>>(),
+        b.systems.iter().map(|s| &s.name).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        a.jumps
+            .iter()
+            .map(|j| (j.from_id, j.to_id))
+            .collect::<Vec<_>>(),
+        b.jumps
+            .iter()
+            .map(|j| (j.from_id, j.to_id))
+            .collect::<Vec<_>>()
+    );
+
+    let other = generate(&GalaxyConfig { seed: 1, ..config });
+    assert_ne!(a.systems[0].name, other.systems[0].name);
+}
+
+#[test]
+fn large_galaxy_is_connected_and_round_trips_through_sqlite() {
+    let starmap = generate(&large());
+    assert_eq!(starmap.systems.len(), 20_000);
+    assert!(is_connected(&starmap));
+    let names: HashSet<&str> = starmap.systems.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names.len(), starmap.systems.len());
+    let regions: HashSet<i64> = starmap.systems.iter().filter_map(|s| s.region_id).collect();
+    assert_eq!(regions.len(), 100);
+
+    let path =
+        std::env::temp_dir().join(format!("evefrontier-synthetic-{}.db", std::process::id()));
+    write_sqlite(&path, &starmap, SyntheticSchema::Full).unwrap();
+    let (systems, jumps) = load_starmap(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(systems.len(), starmap.systems.len());
+    for (loaded, generated) in systems.iter().zip(&starmap.systems) {
+        assert_eq!(loaded.id, generated.id);
+        assert_eq!(loaded.name, generated.name);
+        assert_eq!(loaded.region, generated.region);
+        assert_eq!(loaded.constellation, generated.constellation);
+        assert_eq!(loaded.position, generated.position);
+    }
+    assert_eq!(jumps.len(), starmap.jumps.len());
+
+    let loaded = Starmap::new(systems, jumps);
+    let route = shortest_path(&loaded.graph, 0, loaded.systems.len() - 1).unwrap();
+    assert!(route.len() > 2);
+}
+
+#[test]
+fn minimal_schema_loads_names_and_jumps_only() {
+    let starmap = generate(&GalaxyConfig {
+        systems: 120,
+        regions: 3,
+        constellations_per_region: 4,
+        ..GalaxyConfig::default()
+    });
+    let mut conn = Connection::open_in_memory().unwrap();
+    write_to_connection(&mut conn, &starmap, SyntheticSchema::Minimal).unwrap();
+    let loaded = Starmap::from_connection(&conn).unwrap();
+    assert_eq!(loaded.systems.len(), 120);
+    assert!(loaded
+        .systems
+        .iter()
+        .all(|s| s.region.is_none() && s.position.is_none()));
+    assert!(is_connected(&loaded));
+
+    let route = optimal_route(&loaded.graph, 0);
+    let visited: HashSet<usize> = route.iter().copied().collect();
+    assert_eq!(visited.len(), 120);
+    assert_eq!(route.first(), route.last());
+}
+
+#[test]
+fn tours_visit_every_system_of_a_thousand_system_galaxy() {
+    let starmap = generate(&GalaxyConfig::default());
+    let route = optimal_route(&starmap.graph, 0);
+    let visited: HashSet<usize> = route.iter().copied().collect();
+    assert_eq!(visited.len(), starmap.systems.len());
+    assert_eq!(route.first(), route.last());
+    for pair in route.windows(2) {
+        assert!(shortest_path(&starmap.graph, pair[0], pair[1]).is_some_and(|p| p.len() == 2));
+    }
+}
+
+#[test]
+fn probabilities_outside_zero_to_one_are_clamped() {
+    let gates = |gate: f64, link: f64| {
+        let starmap = generate(&GalaxyConfig {
+            systems: 300,
+            extra_gate_probability: gate,
+            extra_link_probability: link,
+            ..GalaxyConfig::default()
+        });
+        assert!(is_connected(&starmap));
+        starmap
+            .jumps
+            .iter()
+            .map(|j| (j.from_id, j.to_id))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(gates(-0.5, f64::NAN), gates(0.0, 0.0));
+    assert_eq!(gates(1.5, f64::INFINITY), gates(1.0, 1.0));
+    assert!(gates(1.0, 1.0).len() > gates(0.0, 0.0).len());
+}